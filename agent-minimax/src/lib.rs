@@ -1,238 +1,226 @@
 // Connect‑4 AI agent for Turbo environment
 // Fits into:
-//     pub fn agent(state: &GamePublicState, context: &mut TurboActionContext) -> u8
+//     pub fn agent(state: &GamePublicState, context: &mut TurboActionContext) -> GameAction
 // The AI uses a depth‑limited minimax search with alpha–beta pruning.
 // `state.board` is a 6×7 array of u8 where 0 = empty, 1 = player 1 disc, 2 = player 2 disc.
 // `state.current_player` indicates whose turn it is (1 or 2).
 // We ignore `context` as requested.
 
-use game_lib::state::GamePublicState;
+use game_lib::{action::GameAction, state::GamePublicState};
 use turbo_program::context::TurboActionContext;
 
-const ROWS: usize = 6;
-const COLS: usize = 7;
 const MAX_DEPTH: usize = 4; // adjust for stronger/slower play
 
-/// Returns the column (0‑indexed) the agent chooses to drop a disc in.
+// ===== Board wrapper with search / evaluation logic =====
+#[derive(Clone)]
+struct Board {
+    cells: Vec<Vec<u8>>,
+    rows: usize,
+    cols: usize,
+    connect: usize,
+}
+
 #[allow(clippy::needless_range_loop)]
-pub fn agent(state: &GamePublicState, _context: &mut TurboActionContext) -> u8 {
-    // ===== Determine which piece is ours and which is the opponent's =====
-    let my_piece: u8 = state.current_player;
-    let opp_piece: u8 = if my_piece == 1 { 2 } else { 1 };
+impl Board {
+    /// Columns that are not full.
+    fn valid_moves(&self) -> Vec<usize> {
+        (0..self.cols).filter(|&c| self.cells[0][c] == 0).collect()
+    }
 
-    // ===== Helper board wrapper with search / evaluation logic =====
-    #[derive(Clone)]
-    struct Board([[u8; COLS]; ROWS]);
+    /// Returns a new board with `piece` dropped in `col`, or `None` if the column is full.
+    fn drop(&self, col: usize, piece: u8) -> Option<Self> {
+        if col >= self.cols || self.cells[0][col] != 0 {
+            return None;
+        }
+        let mut next = self.clone();
+        for r in (0..self.rows).rev() {
+            if next.cells[r][col] == 0 {
+                next.cells[r][col] = piece;
+                return Some(next);
+            }
+        }
+        None
+    }
 
-    impl Board {
-        /// Columns that are not full.
-        fn valid_moves(&self) -> Vec<usize> {
-            (0..COLS).filter(|&c| self.0[0][c] == 0).collect()
+    /// Number of empty, player 1 and player 2 cells in the `connect`‑cell
+    /// window from (`r`, `c`) walking by (`dr`, `dc`), or `None` if the
+    /// window leaves the board. Reads the cells in place, without allocating.
+    fn count_window(&self, r: usize, c: usize, dr: isize, dc: isize) -> Option<[usize; 3]> {
+        let last = self.connect as isize - 1;
+        let end_r = r as isize + dr * last;
+        let end_c = c as isize + dc * last;
+        if end_r < 0 || end_r >= self.rows as isize || end_c >= self.cols as isize {
+            return None;
         }
 
-        /// Returns a new board with `piece` dropped in `col`, or `None` if the column is full.
-        fn drop(&self, col: usize, piece: u8) -> Option<Self> {
-            if col >= COLS || self.0[0][col] != 0 {
-                return None;
-            }
-            let mut next = self.clone();
-            for r in (0..ROWS).rev() {
-                if next.0[r][col] == 0 {
-                    next.0[r][col] = piece;
-                    return Some(next);
-                }
-            }
-            None
+        let mut counts = [0; 3];
+        for i in 0..=last {
+            let cell = self.cells[(r as isize + dr * i) as usize][(c as isize + dc * i) as usize];
+            counts[cell as usize] += 1;
         }
+        Some(counts)
+    }
 
-        /// Four‑in‑a‑row check for `piece`.
-        fn is_win(&self, piece: u8) -> bool {
-            // Horizontal
-            for r in 0..ROWS {
-                for c in 0..COLS - 3 {
-                    if (0..4).all(|i| self.0[r][c + i] == piece) {
+    /// Calls `f` with the counts of every `connect`‑cell window in all four
+    /// directions (→, ↓, ↘, ↗), stopping once it returns `true`.
+    fn any_window(&self, mut f: impl FnMut([usize; 3]) -> bool) -> bool {
+        for (dr, dc) in [(0isize, 1isize), (1, 0), (1, 1), (-1, 1)] {
+            for r in 0..self.rows {
+                for c in 0..self.cols {
+                    if self.count_window(r, c, dr, dc).is_some_and(&mut f) {
                         return true;
                     }
                 }
             }
-            // Vertical
-            for c in 0..COLS {
-                for r in 0..ROWS - 3 {
-                    if (0..4).all(|i| self.0[r + i][c] == piece) {
-                        return true;
-                    }
-                }
-            }
-            // Diagonal ↘
-            for r in 0..ROWS - 3 {
-                for c in 0..COLS - 3 {
-                    if (0..4).all(|i| self.0[r + i][c + i] == piece) {
-                        return true;
-                    }
-                }
-            }
-            // Diagonal ↗
-            for r in 3..ROWS {
-                for c in 0..COLS - 3 {
-                    if (0..4).all(|i| self.0[r - i][c + i] == piece) {
-                        return true;
-                    }
-                }
-            }
-            false
         }
+        false
+    }
 
-        /// Scores a 4‑cell window for `piece` (heuristic).
-        fn eval_window(window: &[u8; 4], piece: u8) -> i32 {
-            let empty = window.iter().filter(|&&v| v == 0).count();
-            let count_piece = window.iter().filter(|&&v| v == piece).count();
-            match (count_piece, empty) {
-                (4, _) => 1_000,
-                (3, 1) => 5,
-                (2, 2) => 2,
-                _ => 0,
-            }
+    /// Connect‑N check for `piece`.
+    fn is_win(&self, piece: u8) -> bool {
+        self.any_window(|counts| counts[piece as usize] == self.connect)
+    }
+
+    /// Scores a `connect`‑cell window holding `count_piece` cells of a
+    /// piece and `empty` empty cells (heuristic).
+    fn eval_window(&self, count_piece: usize, empty: usize) -> i32 {
+        let n = self.connect;
+        if count_piece == 0 {
+            // Nothing to build on, which short connects would still score
+            0
+        } else if count_piece == n {
+            1_000
+        } else if count_piece + 1 == n && empty == 1 {
+            5
+        } else if count_piece + 2 == n && empty == 2 {
+            2
+        } else {
+            0
         }
+    }
 
-        /// Heuristic board evaluation from `my_piece` POV (positive is good).
-        fn evaluate(&self, my_piece: u8, opp_piece: u8) -> i32 {
-            let mut score = 0;
-            // Center control bonus.
-            let center_col = COLS / 2;
-            let center_count = (0..ROWS)
-                .filter(|&r| self.0[r][center_col] == my_piece)
-                .count();
-            score += (center_count as i32) * 6;
-
-            // Score all 4‑cell windows in every direction.
-            // Horizontal
-            for r in 0..ROWS {
-                for c in 0..COLS - 3 {
-                    let w = [
-                        self.0[r][c],
-                        self.0[r][c + 1],
-                        self.0[r][c + 2],
-                        self.0[r][c + 3],
-                    ];
-                    score += Self::eval_window(&w, my_piece);
-                    score -= Self::eval_window(&w, opp_piece);
-                }
-            }
-            // Vertical
-            for c in 0..COLS {
-                for r in 0..ROWS - 3 {
-                    let w = [
-                        self.0[r][c],
-                        self.0[r + 1][c],
-                        self.0[r + 2][c],
-                        self.0[r + 3][c],
-                    ];
-                    score += Self::eval_window(&w, my_piece);
-                    score -= Self::eval_window(&w, opp_piece);
-                }
-            }
-            // Diagonal ↘
-            for r in 0..ROWS - 3 {
-                for c in 0..COLS - 3 {
-                    let w = [
-                        self.0[r][c],
-                        self.0[r + 1][c + 1],
-                        self.0[r + 2][c + 2],
-                        self.0[r + 3][c + 3],
-                    ];
-                    score += Self::eval_window(&w, my_piece);
-                    score -= Self::eval_window(&w, opp_piece);
-                }
-            }
-            // Diagonal ↗
-            for r in 3..ROWS {
-                for c in 0..COLS - 3 {
-                    let w = [
-                        self.0[r][c],
-                        self.0[r - 1][c + 1],
-                        self.0[r - 2][c + 2],
-                        self.0[r - 3][c + 3],
-                    ];
-                    score += Self::eval_window(&w, my_piece);
-                    score -= Self::eval_window(&w, opp_piece);
+    /// Heuristic board evaluation from `my_piece` POV (positive is good).
+    fn evaluate(&self, my_piece: u8, opp_piece: u8) -> i32 {
+        let mut score = 0;
+        // Center control bonus.
+        let center_col = self.cols / 2;
+        let center_count = (0..self.rows)
+            .filter(|&r| self.cells[r][center_col] == my_piece)
+            .count();
+        score += (center_count as i32) * 6;
+
+        // Score all windows in every direction.
+        self.any_window(|counts| {
+            score += self.eval_window(counts[my_piece as usize], counts[0]);
+            score -= self.eval_window(counts[opp_piece as usize], counts[0]);
+            false
+        });
+        score
+    }
+
+    /// Minimax + alpha–beta. Returns (score, best_col).
+    fn minimax(
+        &self,
+        depth: usize,
+        mut alpha: i32,
+        mut beta: i32,
+        maximizing: bool,
+        my_piece: u8,
+        opp_piece: u8,
+    ) -> (i32, Option<usize>) {
+        let moves = self.valid_moves();
+        let terminal = self.is_win(my_piece) || self.is_win(opp_piece) || moves.is_empty();
+        if depth == 0 || terminal {
+            let val = if terminal {
+                if self.is_win(my_piece) {
+                    1_000_000
+                } else if self.is_win(opp_piece) {
+                    -1_000_000
+                } else {
+                    0 // draw or full board
                 }
-            }
-            score
+            } else {
+                self.evaluate(my_piece, opp_piece)
+            };
+            return (val, None);
         }
 
-        /// Minimax + alpha–beta. Returns (score, best_col).
-        fn minimax(
-            &self,
-            depth: usize,
-            mut alpha: i32,
-            mut beta: i32,
-            maximizing: bool,
-            my_piece: u8,
-            opp_piece: u8,
-        ) -> (i32, Option<usize>) {
-            let moves = self.valid_moves();
-            let terminal = self.is_win(my_piece) || self.is_win(opp_piece) || moves.is_empty();
-            if depth == 0 || terminal {
-                let val = if terminal {
-                    if self.is_win(my_piece) {
-                        1_000_000
-                    } else if self.is_win(opp_piece) {
-                        -1_000_000
-                    } else {
-                        0 // draw or full board
+        let mut best_col = None;
+        if maximizing {
+            let mut value = i32::MIN;
+            for col in moves {
+                if let Some(next) = self.drop(col, my_piece) {
+                    let (score, _) =
+                        next.minimax(depth - 1, alpha, beta, false, my_piece, opp_piece);
+                    if score > value {
+                        value = score;
+                        best_col = Some(col);
                     }
-                } else {
-                    self.evaluate(my_piece, opp_piece)
-                };
-                return (val, None);
-            }
-
-            let mut best_col = None;
-            if maximizing {
-                let mut value = i32::MIN;
-                for col in moves {
-                    if let Some(next) = self.drop(col, my_piece) {
-                        let (score, _) =
-                            next.minimax(depth - 1, alpha, beta, false, my_piece, opp_piece);
-                        if score > value {
-                            value = score;
-                            best_col = Some(col);
-                        }
-                        alpha = alpha.max(value);
-                        if alpha >= beta {
-                            break; // β cut‑off
-                        }
+                    alpha = alpha.max(value);
+                    if alpha >= beta {
+                        break; // β cut‑off
                     }
                 }
-                (value, best_col)
-            } else {
-                let mut value = i32::MAX;
-                for col in moves {
-                    if let Some(next) = self.drop(col, opp_piece) {
-                        let (score, _) =
-                            next.minimax(depth - 1, alpha, beta, true, my_piece, opp_piece);
-                        if score < value {
-                            value = score;
-                            best_col = Some(col);
-                        }
-                        beta = beta.min(value);
-                        if alpha >= beta {
-                            break; // α cut‑off
-                        }
+            }
+            (value, best_col)
+        } else {
+            let mut value = i32::MAX;
+            for col in moves {
+                if let Some(next) = self.drop(col, opp_piece) {
+                    let (score, _) =
+                        next.minimax(depth - 1, alpha, beta, true, my_piece, opp_piece);
+                    if score < value {
+                        value = score;
+                        best_col = Some(col);
+                    }
+                    beta = beta.min(value);
+                    if alpha >= beta {
+                        break; // α cut‑off
                     }
                 }
-                (value, best_col)
             }
+            (value, best_col)
         }
+    }
 
-        /// Top‑level helper that picks the best column for `my_piece`.
-        fn best_move(&self, my_piece: u8, opp_piece: u8) -> usize {
-            let (_, col) = self.minimax(MAX_DEPTH, i32::MIN, i32::MAX, true, my_piece, opp_piece);
-            col.unwrap_or_else(|| *self.valid_moves().first().unwrap_or(&0))
-        }
+    /// Top‑level helper that picks the best column for `my_piece`.
+    fn best_move(&self, my_piece: u8, opp_piece: u8) -> usize {
+        let (_, col) = self.minimax(MAX_DEPTH, i32::MIN, i32::MAX, true, my_piece, opp_piece);
+        col.unwrap_or_else(|| *self.valid_moves().first().unwrap_or(&0))
     }
+}
+
+/// Returns the column (0‑indexed) the agent chooses to drop a disc in.
+pub fn agent(state: &GamePublicState, _context: &mut TurboActionContext) -> GameAction {
+    // ===== Determine which piece is ours and which is the opponent's =====
+    let my_piece: u8 = state.current_player;
+    let opp_piece: u8 = if my_piece == 1 { 2 } else { 1 };
 
     // Run the search from the current position and return the column.
-    let root = Board(state.board);
-    root.best_move(my_piece, opp_piece) as u8
+    let root = Board {
+        cells: state.board.clone(),
+        rows: state.config.rows as usize,
+        cols: state.config.columns as usize,
+        connect: state.config.connect as usize,
+    };
+    GameAction::DropPiece(root.best_move(my_piece, opp_piece) as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_windows_score_nothing_on_short_connects() {
+        let board = Board {
+            cells: vec![vec![0; 3]; 3],
+            rows: 3,
+            cols: 3,
+            connect: 2,
+        };
+
+        assert_eq!(board.eval_window(0, 2), 0);
+        assert_eq!(board.eval_window(1, 1), 5);
+        assert_eq!(board.eval_window(2, 0), 1_000);
+    }
 }
@@ -1,15 +1,15 @@
-use game_lib::state::GamePublicState;
+use game_lib::{action::GameAction, state::GamePublicState};
 use turbo_program::context::TurboActionContext;
 
-pub fn agent(state: &GamePublicState, context: &mut TurboActionContext) -> u8 {
+pub fn agent(state: &GamePublicState, context: &mut TurboActionContext) -> GameAction {
     // Check if a valid move is available
     if state.winner != 0 {
-        return 0;
+        return GameAction::DropPiece(0);
     }
 
     // Create a list of empty columns
     let mut empty_columns = Vec::new();
-    for col in 0..7 {
+    for col in 0..state.config.columns as usize {
         if state.board[0][col] == 0 {
             empty_columns.push(col);
         }
@@ -17,5 +17,5 @@ pub fn agent(state: &GamePublicState, context: &mut TurboActionContext) -> u8 {
 
     // Get a random move
     let idx = (context.rand_u32() % empty_columns.len() as u32) as usize;
-    empty_columns[idx] as u8
+    GameAction::DropPiece(empty_columns[idx] as u8)
 }
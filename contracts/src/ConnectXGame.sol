@@ -4,13 +4,39 @@ pragma solidity ^0.8.20;
 import {ISP1Verifier} from "@sp1-contracts/ISP1Verifier.sol";
 import {FixedPointMathLib} from "@solmate/utils/FixedPointMathLib.sol";
 
+struct GameConfig {
+    uint8 rows; // board height
+    uint8 columns; // board width
+    uint8 connect; // pieces in a row needed to win
+}
+
 struct GamePublicState {
-    uint8[7][6] board; // 7 columns, 6 rows for Connect 4
+    GameConfig config; // board dimensions and win length
+    uint8[][] board; // rows x columns, indexed [row][column]
     uint8 currentPlayer; // 1 for player 1, 2 for player 2
     uint8 winner; // 0 for no winner, 1 for player 1, 2 for player 2
     uint8[] moves;
 }
 
+/// @notice Public values committed by the game program.
+struct TurboPublicValues {
+    bytes32 matchNonce; // nonce picked by the server for this match
+    bytes32 matchDigest; // keccak256 of the seeds, nonce, seat owners, game config and action stream
+    bytes32 serverSeedHash; // keccak256 of the server seed point
+    bytes32[] playerSeedHashes; // keccak256 of each player seed point
+    address[] playerAddresses; // address owning each seat, zero if anonymous
+    bytes32[] actionHashes; // final action hash of each player context
+    bytes32 privateStateHash; // keccak256 of the server seed and ABI encoded private state
+    bytes publicState; // ABI encoded GamePublicState
+}
+
+/// @notice Public values committed by the agent programs.
+struct AgentPublicValues {
+    bytes32 matchDigest; // must match the digest of the game proof
+    uint8 playerIndex; // seat the agent played
+    bytes actions; // moves of the match
+}
+
 struct Agent {
     bytes32 vkey;
     address owner;
@@ -38,6 +64,9 @@ contract ConnectXGame {
     /// @notice Agent registry
     mapping(bytes32 => Agent) public agentRegistry;
 
+    /// @notice Match digests that were already played
+    mapping(bytes32 => bool) public playedMatches;
+
     /// @notice K-factor for ELO calculation
     uint256 public constant K_FACTOR = 32;
 
@@ -160,10 +189,17 @@ contract ConnectXGame {
         emit GameResult(_agent1, _agent2, _winner, agent1.elo, agent2.elo);
     }
 
-    function toBytes(uint8[] memory arr) internal pure returns (bytes memory) {
-        bytes memory b = new bytes(arr.length);
-        for (uint i = 0; i < arr.length; i++) {
-            b[i] = bytes1(arr[i]);
+    /// @notice Encode moves as a length-delimited action stream
+    /// @dev Every action is a big-endian uint16 length followed by its bytes,
+    ///      a ConnectX move is a single byte holding the column.
+    function toActionStream(
+        uint8[] memory moves
+    ) internal pure returns (bytes memory) {
+        bytes memory b = new bytes(moves.length * 3);
+        for (uint i = 0; i < moves.length; i++) {
+            b[i * 3] = 0x00;
+            b[i * 3 + 1] = 0x01;
+            b[i * 3 + 2] = bytes1(moves[i]);
         }
         return b;
     }
@@ -186,8 +222,12 @@ contract ConnectXGame {
         );
 
         // Verify moves in game public values and moves
-        GamePublicState memory gamePublicState = abi.decode(
+        TurboPublicValues memory gamePublicValues = abi.decode(
             _gamePublicValues,
+            (TurboPublicValues)
+        );
+        GamePublicState memory gamePublicState = abi.decode(
+            gamePublicValues.publicState,
             (GamePublicState)
         );
 
@@ -198,19 +238,30 @@ contract ConnectXGame {
             _gameProof
         );
 
-        bytes memory moves = toBytes(gamePublicState.moves);
+        // Reject replayed matches
+        require(
+            !playedMatches[gamePublicValues.matchDigest],
+            "Match already played"
+        );
+        playedMatches[gamePublicValues.matchDigest] = true;
+
+        bytes memory moves = toActionStream(gamePublicState.moves);
 
         // Verify agent1 proof
         ISP1Verifier(verifier).verifyProof(
             agentRegistry[_agent1].vkey,
-            moves,
+            abi.encode(
+                AgentPublicValues(gamePublicValues.matchDigest, 0, moves)
+            ),
             _agent1proof
         );
 
         // Verify agent2 proof
         ISP1Verifier(verifier).verifyProof(
             agentRegistry[_agent2].vkey,
-            moves,
+            abi.encode(
+                AgentPublicValues(gamePublicValues.matchDigest, 1, moves)
+            ),
             _agent2proof
         );
 
@@ -1,15 +1,25 @@
 use serde_json::Value;
 use turbo_program::traits::TurboActionSerialization;
 
+use crate::error::GameError;
+
 #[derive(Debug)]
 pub enum GameAction {
-    DropPiece(u8), // Column number (0-6) where to drop the piece
+    DropPiece(u8), // Column number where to drop the piece, bounded by the game config
 }
 
 impl TurboActionSerialization for GameAction {
+    type ReducerError = GameError;
+
     fn deserialize(action: &[u8]) -> Result<(Self, &[u8]), &'static str> {
-        let column = action[0];
-        Ok((GameAction::DropPiece(column), &action[1..]))
+        let (&column, rest) = action.split_first().ok_or("Empty action")?;
+        Ok((GameAction::DropPiece(column), rest))
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        match self {
+            GameAction::DropPiece(column) => vec![*column],
+        }
     }
 
     fn serialize_json(json_str: &str) -> Result<Vec<u8>, &'static str> {
@@ -27,11 +37,12 @@ impl TurboActionSerialization for GameAction {
                     if data.len() != 1 {
                         return Err("Invalid data length for DropPiece");
                     }
-                    let column = data[0].as_u64().ok_or("Invalid column")? as u8;
-                    if column >= 7 {
+                    // The board width comes from the game config, the reducer checks the bounds
+                    let column = data[0].as_u64().ok_or("Invalid column")?;
+                    if column > u8::MAX as u64 {
                         return Err("Column out of bounds");
                     }
-                    result.push(column);
+                    result.push(column as u8);
                 }
                 _ => return Err("Invalid action type"),
             }
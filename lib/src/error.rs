@@ -0,0 +1,23 @@
+/// Reasons the reducer rejects an action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameError {
+    /// The action was sent by the player not on turn.
+    WrongPlayer,
+    /// The game already has a winner or is a draw.
+    GameOver,
+    /// The column is outside the board.
+    InvalidColumn,
+    /// The column has no empty cell left.
+    ColumnFull,
+}
+
+impl From<GameError> for &'static str {
+    fn from(error: GameError) -> Self {
+        match error {
+            GameError::WrongPlayer => "Not your turn",
+            GameError::GameOver => "Game is over",
+            GameError::InvalidColumn => "Column out of bounds",
+            GameError::ColumnFull => "Column is full",
+        }
+    }
+}
@@ -1,4 +1,5 @@
 pub mod action;
+pub mod error;
 pub mod hook;
 pub mod reducer;
 pub mod state;
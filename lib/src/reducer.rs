@@ -1,89 +1,56 @@
 use serde_json::json;
 use turbo_program::context::TurboActionContext;
 
-use crate::{action::GameAction, state::GamePrivateState, state::GamePublicState};
-
-/// Check if there's a winner in the current board state
-fn check_winner(board: &[[u8; 7]; 6], row: usize, col: usize, player: u8) -> bool {
-    // Check horizontal
+use crate::{
+    action::GameAction, error::GameError, state::GamePrivateState, state::GamePublicState,
+};
+
+/// Count consecutive `player` pieces from (`row`, `col`) walking by (`dr`, `dc`),
+/// not counting the starting cell
+fn count_direction(
+    board: &[Vec<u8>],
+    row: usize,
+    col: usize,
+    dr: i32,
+    dc: i32,
+    player: u8,
+) -> usize {
     let mut count = 0;
-    for c in 0..7 {
-        if board[row][c] == player {
-            count += 1;
-            if count >= 4 {
-                return true;
-            }
-        } else {
-            count = 0;
-        }
+    let mut r = row as i32 + dr;
+    let mut c = col as i32 + dc;
+
+    while r >= 0
+        && c >= 0
+        && (r as usize) < board.len()
+        && (c as usize) < board[r as usize].len()
+        && board[r as usize][c as usize] == player
+    {
+        count += 1;
+        r += dr;
+        c += dc;
     }
 
-    // Check vertical
-    count = 0;
-    for r in 0..6 {
-        if board[r][col] == player {
-            count += 1;
-            if count >= 4 {
-                return true;
-            }
-        } else {
-            count = 0;
-        }
-    }
-
-    // Check diagonal (top-left to bottom-right)
-    let mut r = row as i32;
-    let mut c = col as i32;
-    while r > 0 && c > 0 {
-        r -= 1;
-        c -= 1;
-    }
-    count = 0;
-    while r < 6 && c < 7 {
-        if board[r as usize][c as usize] == player {
-            count += 1;
-            if count >= 4 {
-                return true;
-            }
-        } else {
-            count = 0;
-        }
-        r += 1;
-        c += 1;
-    }
+    count
+}
 
-    // Check diagonal (top-right to bottom-left)
-    r = row as i32;
-    c = col as i32;
-    while r > 0 && c < 6 {
-        r -= 1;
-        c += 1;
-    }
-    count = 0;
-    while r < 6 && c >= 0 {
-        if board[r as usize][c as usize] == player {
-            count += 1;
-            if count >= 4 {
-                return true;
-            }
-        } else {
-            count = 0;
+/// Check if the piece placed at (`row`, `col`) completes a line of `connect`
+fn check_winner(board: &[Vec<u8>], row: usize, col: usize, player: u8, connect: usize) -> bool {
+    // Horizontal, vertical and both diagonals
+    for (dr, dc) in [(0, 1), (1, 0), (1, 1), (1, -1)] {
+        let count = 1
+            + count_direction(board, row, col, dr, dc, player)
+            + count_direction(board, row, col, -dr, -dc, player);
+        if count >= connect {
+            return true;
         }
-        r += 1;
-        c -= 1;
     }
 
     false
 }
 
 /// Check if the board is full
-fn is_board_full(board: &[[u8; 7]; 6]) -> bool {
-    for col in 0..7 {
-        if board[0][col] == 0 {
-            return false;
-        }
-    }
-    true
+fn is_board_full(board: &[Vec<u8>]) -> bool {
+    board[0].iter().all(|cell| *cell != 0)
 }
 
 pub fn reducer(
@@ -91,7 +58,7 @@ pub fn reducer(
     private_state: &mut GamePrivateState,
     action: &GameAction,
     context: &mut TurboActionContext,
-) {
+) -> Result<(), GameError> {
     match action {
         GameAction::DropPiece(column) => {
             #[cfg(not(target_os = "zkvm"))]
@@ -99,30 +66,30 @@ pub fn reducer(
                 *context.client_response() = None;
             }
 
-            // Skip if wrong player move
+            // Reject moves out of turn
             if public_state.current_player != context.player_index() as u8 + 1 {
-                return;
+                return Err(GameError::WrongPlayer);
             }
 
-            // Skip if the game is already won
+            // Reject moves once the game is won or drawn
             if public_state.winner != 0 {
-                return;
+                return Err(GameError::GameOver);
             }
 
             // Validate column
-            if *column >= 7 {
-                return;
+            if *column >= public_state.config.columns {
+                return Err(GameError::InvalidColumn);
             }
 
             // Find the lowest empty row in the selected column
-            let mut row = 5;
+            let mut row = public_state.config.rows as usize - 1;
             while row > 0 && public_state.board[row][*column as usize] != 0 {
                 row -= 1;
             }
 
-            // If column is full, return
+            // Reject full columns
             if public_state.board[row][*column as usize] != 0 {
-                return;
+                return Err(GameError::ColumnFull);
             }
 
             // Place the piece
@@ -140,6 +107,7 @@ pub fn reducer(
                 row,
                 *column as usize,
                 public_state.current_player,
+                public_state.config.connect as usize,
             ) {
                 public_state.winner = public_state.current_player;
             } else if is_board_full(&public_state.board) {
@@ -167,4 +135,6 @@ pub fn reducer(
             }
         }
     }
+
+    Ok(())
 }
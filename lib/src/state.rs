@@ -1,26 +1,82 @@
 use alloy_sol_types::sol;
 use serde::{Deserialize, Serialize};
-use turbo_program::traits::{HasActions, HasTerminalState};
+use turbo_program::{
+    action_stream::encode_actions,
+    traits::{
+        HasActions, HasCurrentPlayer, HasTerminalState, HasWinner, PlayerView, TurboInitState,
+        UndoRules,
+    },
+};
 
 sol! {
+    #[derive(Serialize, Deserialize, Debug)]
+    struct GameConfig {
+        uint8 rows;  // board height
+        uint8 columns;  // board width
+        uint8 connect;  // pieces in a row needed to win
+    }
+
     #[derive(Serialize, Deserialize, Debug)]
     struct GamePublicState {
-        uint8[7][6] board;  // 7 columns, 6 rows for Connect 4
+        GameConfig config;
+        uint8[][] board;  // `config.rows` rows of `config.columns` cells, row 0 is the top
         uint8 current_player;  // 1 for player 1, 2 for player 2
         uint8 winner;  // 0 for no winner, 1 for player 1, 2 for player 2
         uint8[] moves;
     }
-}
 
-#[derive(Serialize, Deserialize, Debug, Default)]
-pub struct GamePrivateState {
-    pub moves: u32,
+    /// Hidden state of the game, only committed as a hash in the proof.
+    #[derive(Serialize, Deserialize, Debug, Default)]
+    struct GamePrivateState {
+        uint32 moves;
+    }
 }
 
-impl Default for GamePublicState {
+impl Default for GameConfig {
     fn default() -> Self {
+        // Connect Four
         Self {
-            board: [[0; 7]; 6],
+            rows: 6,
+            columns: 7,
+            connect: 4,
+        }
+    }
+}
+
+impl GameConfig {
+    /// Parse a `[rows, columns, connect]` config, empty for the default board.
+    pub fn from_bytes(config: &[u8]) -> Result<Self, &'static str> {
+        let config = match config {
+            [] => Self::default(),
+            [rows, columns, connect] => Self {
+                rows: *rows,
+                columns: *columns,
+                connect: *connect,
+            },
+            _ => return Err("Invalid game config length"),
+        };
+
+        if config.rows == 0 || config.columns == 0 {
+            return Err("Board must have at least one row and column");
+        }
+
+        if config.connect == 0 || config.connect > config.rows.max(config.columns) {
+            return Err("Connect length does not fit the board");
+        }
+
+        Ok(config)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        vec![self.rows, self.columns, self.connect]
+    }
+}
+
+impl GamePublicState {
+    pub fn new(config: GameConfig) -> Self {
+        Self {
+            board: vec![vec![0; config.columns as usize]; config.rows as usize],
+            config,
             current_player: 1,
             winner: 0,
             moves: vec![],
@@ -28,14 +84,66 @@ impl Default for GamePublicState {
     }
 }
 
+impl Default for GamePublicState {
+    fn default() -> Self {
+        Self::new(GameConfig::default())
+    }
+}
+
+impl TurboInitState for GamePublicState {
+    fn init_state(config: &[u8]) -> Result<Self, &'static str> {
+        Ok(Self::new(GameConfig::from_bytes(config)?))
+    }
+
+    fn max_players(&self) -> Option<usize> {
+        Some(2)
+    }
+}
+
+impl PlayerView<GamePrivateState> for GamePublicState {
+    fn player_view(
+        &self,
+        _private_state: &GamePrivateState,
+        _player_idx: usize,
+    ) -> serde_json::Value {
+        // Connect Four has no hidden information
+        serde_json::to_value(self).unwrap_or_default()
+    }
+
+    fn spectator_view(&self, _private_state: &GamePrivateState) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+}
+
+impl UndoRules for GamePublicState {
+    fn can_undo(&self, _player_idx: usize) -> bool {
+        // Moves can be taken back until the game is decided
+        !self.is_terminal()
+    }
+}
+
 impl HasTerminalState for GamePublicState {
     fn is_terminal(&self) -> bool {
         self.winner != 0
     }
 }
 
+impl HasWinner for GamePublicState {
+    fn winner(&self) -> Option<usize> {
+        // 3 is a draw
+        matches!(self.winner, 1 | 2).then(|| self.winner as usize - 1)
+    }
+}
+
 impl HasActions for GamePublicState {
     fn actions(&self) -> Vec<u8> {
-        self.moves.clone()
+        encode_actions(self.moves.iter().map(std::slice::from_ref))
+    }
+}
+
+impl HasCurrentPlayer for GamePublicState {
+    fn current_player(&self) -> Option<usize> {
+        // 0 for a state that was never initialized
+        matches!(self.current_player, 1 | 2).then(|| self.current_player as usize - 1)
     }
 }
@@ -1,17 +1,37 @@
+use std::path::PathBuf;
 use std::sync::Arc;
 
+use clap::Parser;
 use game_lib::reducer::reducer;
 use game_utils::print::print_public_state;
 use sp1_sdk::{include_elf, ProverClient};
-use turbo_sp1::zeromind::{zeromind_submit_agent, ZeromindAgentSubmission};
+use turbo_sp1::zeromind::{
+    zeromind_submit_agent_with_options, SubmitMode, SubmitOptions, ZeromindAgentSubmission,
+    ZeromindConfig,
+};
 
 /// The ELF (executable and linkable format) file for the Succinct RISC-V zkVM.
 pub const GAME_ELF: &[u8] = include_elf!("game-program");
 pub const AGENT_RANDOM_ELF: &[u8] = include_elf!("agent-random");
 pub const AGENT_MINIMAX_ELF: &[u8] = include_elf!("agent-minimax");
 
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// Stop after executing and cross checking the game and agent programs.
+    #[arg(long)]
+    check_only: bool,
+
+    /// Write the proofs and the `playGame` calldata to this directory instead
+    /// of sending transactions.
+    #[arg(long, conflicts_with = "check_only")]
+    dry_run: Option<PathBuf>,
+}
+
 #[tokio::main]
 async fn main() {
+    let args = Args::parse();
+
     // ========= CONFIG YOUR AGENTS HERE =========
 
     let your_agent =
@@ -26,16 +46,34 @@ async fn main() {
     dotenv::dotenv().ok();
 
     let client = Arc::new(ProverClient::from_env());
+    let config = ZeromindConfig::from_env().expect("Failed to configure ZeroMind");
+
+    let mode = match args.dry_run {
+        Some(dir) => SubmitMode::DryRun { dir },
+        None if args.check_only => SubmitMode::CheckOnly,
+        None => SubmitMode::OnChain,
+    };
 
-    let public_state = zeromind_submit_agent(
+    let public_state = match zeromind_submit_agent_with_options(
         client,
+        &config,
         reducer,
         Arc::new(GAME_ELF.to_vec()),
         your_agent,
         opponent_agent,
+        SubmitOptions {
+            mode,
+            ..Default::default()
+        },
     )
     .await
-    .unwrap();
+    {
+        Ok(public_state) => public_state,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
     print_public_state(&public_state);
 
     println!("Moves: {:?}", public_state.moves);
@@ -16,6 +16,7 @@ use sp1_sdk::{include_elf, HashableKey, ProverClient, SP1Stdin, SP1VerifyingKey}
 use std::num::ParseIntError;
 use std::path::PathBuf;
 use std::str::FromStr;
+use turbo_sp1::key_cache::KeyCache;
 
 /// The ELF (executable and linkable format) file for the Succinct RISC-V zkVM.
 pub const GAME_ELF: &[u8] = include_elf!("game-program");
@@ -79,7 +80,10 @@ fn main() {
 
     // Setup the program for proving.
     let setup_start = std::time::Instant::now();
-    let (pk, vk) = client.setup(GAME_ELF);
+    let keys = KeyCache::global()
+        .get_or_setup(GAME_ELF, || client.setup(GAME_ELF))
+        .expect("failed to set up keys");
+    let (pk, vk) = &*keys;
     let setup_duration = setup_start.elapsed();
     println!("Setup completed in: {:?}", setup_duration);
 
@@ -87,8 +91,8 @@ fn main() {
     let prove_start = std::time::Instant::now();
     // Generate the proof based on the selected proof system.
     let proof = match args.system {
-        ProofSystem::Plonk => client.prove(&pk, &stdin).plonk().run(),
-        ProofSystem::Groth16 => client.prove(&pk, &stdin).groth16().run(),
+        ProofSystem::Plonk => client.prove(pk, &stdin).plonk().run(),
+        ProofSystem::Groth16 => client.prove(pk, &stdin).groth16().run(),
     }
     .expect("failed to generate proof");
     let prove_duration = prove_start.elapsed();
@@ -111,7 +115,7 @@ fn main() {
 
     // Verify the proof.
     let verify_start = std::time::Instant::now();
-    client.verify(&proof, &vk).expect("failed to verify proof");
+    client.verify(&proof, vk).expect("failed to verify proof");
     let verify_duration = verify_start.elapsed();
     println!("Successfully verified proof in: {:?}", verify_duration);
 
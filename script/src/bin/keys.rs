@@ -0,0 +1,37 @@
+//! Set up the keys of every program in the workspace and store them in the key
+//! cache, so the server, the scripts and ZeroMind submissions skip setup.
+//!
+//! ```shell
+//! cargo run --release --bin keys
+//! ```
+
+use sp1_sdk::{include_elf, HashableKey, ProverClient};
+use turbo_sp1::key_cache::{elf_digest, KeyCache};
+
+/// The ELF (executable and linkable format) files for the Succinct RISC-V zkVM.
+pub const ELFS: [(&str, &[u8]); 3] = [
+    ("game-program", include_elf!("game-program")),
+    ("agent-random", include_elf!("agent-random")),
+    ("agent-minimax", include_elf!("agent-minimax")),
+];
+
+fn main() {
+    sp1_sdk::utils::setup_logger();
+    dotenv::dotenv().ok();
+
+    let client = ProverClient::from_env();
+    for (name, elf) in ELFS {
+        let setup_start = std::time::Instant::now();
+        let keys = KeyCache::global()
+            .get_or_setup(elf, || client.setup(elf))
+            .expect("failed to set up keys");
+
+        println!(
+            "{}: digest 0x{} vkey {} ({:?})",
+            name,
+            hex::encode(elf_digest(elf)),
+            keys.1.bytes32(),
+            setup_start.elapsed()
+        );
+    }
+}
@@ -10,18 +10,19 @@
 //! RUST_LOG=info cargo run --release -- --prove
 //! ```
 
-use alloy_sol_types::SolType;
 use clap::Parser;
 use game_lib::state::GamePublicState;
 use game_utils::print::print_public_state;
-use rand::thread_rng;
+use rand::{thread_rng, Rng};
 use sp1_sdk::{include_elf, ProverClient, SP1Stdin};
 use std::num::ParseIntError;
 use std::str::FromStr;
 use substrate_bn::*;
 use turbo_sp1::{
     crypto::bn_serialize::bn254_export_affine_g1_memcpy,
+    key_cache::KeyCache,
     metadata::{PlayerMetadata, ServerMetadata},
+    proof::{commitments_json, decode_public_values},
 };
 
 /// The ELF (executable and linkable format) file for the Succinct RISC-V zkVM.
@@ -57,6 +58,10 @@ struct Args {
         default_value = "0,2, 1,1, 0,0, 1,2, 0,3, 1,1, 0,3, 1,0, 0,2, 1,1"
     )]
     actions: VecString,
+
+    /// Board rows, columns and connect length.
+    #[arg(long, default_value = "6,7,4")]
+    config: VecString,
 }
 
 fn main() {
@@ -82,9 +87,12 @@ fn main() {
     // Setup mock server and client metadata
     let server_metadata = ServerMetadata {
         random_seed: bn254_export_affine_g1_memcpy(&server_random_seed),
+        match_nonce: rng.gen(),
+        game_config: args.config.0.clone(),
     };
     let player_metadata: PlayerMetadata = PlayerMetadata {
         random_seed: bn254_export_affine_g1_memcpy(&player_random_seed),
+        address: [0; 20],
     };
     let player_metadatas = vec![player_metadata.clone(), player_metadata.clone()];
 
@@ -106,23 +114,28 @@ fn main() {
         println!("Program executed successfully.");
 
         // Read the output.
-        let decoded: GamePublicState = GamePublicState::abi_decode(output.as_slice()).unwrap();
+        let (envelope, decoded) =
+            decode_public_values::<GamePublicState>(output.as_slice()).unwrap();
         print_public_state(&decoded);
         println!("moves: {:?}", decoded.moves);
+        println!("commitments: {}", commitments_json(&envelope));
 
         // Record the number of cycles executed.
         println!("Number of cycles: {}", report.total_instruction_count());
     } else {
         // Setup the program for proving.
         let setup_start = std::time::Instant::now();
-        let (pk, vk) = client.setup(GAME_ELF);
+        let keys = KeyCache::global()
+            .get_or_setup(GAME_ELF, || client.setup(GAME_ELF))
+            .expect("failed to set up keys");
+        let (pk, vk) = &*keys;
         let setup_duration = setup_start.elapsed();
         println!("Setup completed in: {:?}", setup_duration);
 
         // Generate the proof
         let prove_start = std::time::Instant::now();
         let proof = client
-            .prove(&pk, &stdin)
+            .prove(pk, &stdin)
             .run()
             .expect("failed to generate proof");
         let prove_duration = prove_start.elapsed();
@@ -135,15 +148,16 @@ fn main() {
 
         // Verify the proof.
         let verify_start = std::time::Instant::now();
-        client.verify(&proof, &vk).expect("failed to verify proof");
+        client.verify(&proof, vk).expect("failed to verify proof");
         let verify_duration = verify_start.elapsed();
         println!("Successfully verified proof in: {:?}", verify_duration);
 
         // Read the output.
-        let decoded: GamePublicState =
-            GamePublicState::abi_decode(proof.public_values.as_slice()).unwrap();
+        let (envelope, decoded) =
+            decode_public_values::<GamePublicState>(proof.public_values.as_slice()).unwrap();
         print_public_state(&decoded);
         println!("moves: {:?}", decoded.moves);
+        println!("commitments: {}", commitments_json(&envelope));
 
         println!(
             "Total proving time: {:?}",
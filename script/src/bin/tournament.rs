@@ -0,0 +1,58 @@
+//! Rank agents offline by playing a tournament between them on the host.
+//!
+//! ```shell
+//! cargo run --release --bin tournament -- --games 4 --swiss 3
+//! ```
+
+use clap::Parser;
+use game_lib::reducer::reducer;
+use turbo_sp1::zeromind::tournament::{
+    run_tournament, TournamentAgent, TournamentConfig, TournamentFormat,
+};
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// Games per pairing, alternating who moves first.
+    #[arg(long, default_value_t = 2)]
+    games: usize,
+
+    /// Play this many Swiss rounds instead of a round robin.
+    #[arg(long)]
+    swiss: Option<usize>,
+
+    /// Seed of the match seeds, the same seed replays the same tournament.
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+}
+
+fn main() {
+    let args = Args::parse();
+
+    // ========= CONFIG YOUR AGENTS HERE =========
+
+    let agents = [
+        TournamentAgent::new("Minimax", agent_minimax::agent),
+        TournamentAgent::new("Random", agent_random::agent),
+    ];
+
+    // ===========================================
+
+    let config = TournamentConfig {
+        format: match args.swiss {
+            Some(rounds) => TournamentFormat::Swiss { rounds },
+            None => TournamentFormat::RoundRobin,
+        },
+        games_per_pairing: args.games,
+        seed: args.seed,
+        ..Default::default()
+    };
+
+    match run_tournament(reducer, &agents, &config) {
+        Ok(result) => println!("{}", result),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
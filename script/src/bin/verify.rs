@@ -0,0 +1,102 @@
+//! Check a match transcript offline: re-execute the match natively, compare
+//! the result with the recorded state and public values, and verify the proof.
+//!
+//! ```shell
+//! cargo run --release --bin verify -- --transcript proofs/<id>.transcript
+//! ```
+
+use alloy_sol_types::SolValue;
+use clap::Parser;
+use game_lib::reducer::reducer;
+use game_lib::state::GamePublicState;
+use game_utils::print::print_public_state;
+use sp1_sdk::{include_elf, HashableKey, Prover, ProverClient};
+use std::panic::{self, AssertUnwindSafe};
+use std::process::exit;
+use turbo_sp1::{
+    program::{compute_public_values, InvalidActionPolicy},
+    proof::{commitments_json, decode_public_values},
+    rand::provably_fair::is_valid_seed,
+    transcript::Transcript,
+};
+
+/// The ELF (executable and linkable format) file for the Succinct RISC-V zkVM.
+pub const GAME_ELF: &[u8] = include_elf!("game-program");
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// Transcript file, binary or JSON.
+    #[arg(long)]
+    transcript: String,
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("Error: {}", message);
+    exit(1);
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let transcript = Transcript::load(&args.transcript).unwrap_or_else(|e| fail(e));
+
+    if !is_valid_seed(&transcript.server_metadata.random_seed) {
+        fail("Invalid server seed");
+    }
+    if transcript
+        .player_metadata
+        .iter()
+        .any(|metadata| !is_valid_seed(&metadata.random_seed))
+    {
+        fail("Invalid player seed");
+    }
+
+    // Re-execute the match with the same reducer as the game program, which
+    // panics on an action log it would refuse to prove
+    let public_values = panic::catch_unwind(AssertUnwindSafe(|| {
+        compute_public_values(
+            reducer,
+            InvalidActionPolicy::Abort,
+            &transcript.server_metadata,
+            &transcript.player_metadata,
+            &transcript.actions,
+        )
+    }))
+    .unwrap_or_else(|_| fail("Match does not re-execute"));
+    let encoded = public_values.abi_encode();
+
+    if !transcript.public_state.is_empty()
+        && transcript.public_state != public_values.public_state.to_vec()
+    {
+        fail("Recorded public state does not match the re-executed match");
+    }
+    println!("Match re-executed successfully.");
+
+    let (envelope, state) =
+        decode_public_values::<GamePublicState>(&encoded).unwrap_or_else(|e| fail(e));
+    print_public_state(&state);
+    println!("commitments: {}", commitments_json(&envelope));
+
+    let Some(proof) = &transcript.proof else {
+        println!("Transcript has no proof, skipping verification.");
+        return;
+    };
+
+    if proof.proof.public_values.as_slice() != encoded.as_slice() {
+        fail("Proof public values do not match the re-executed match");
+    }
+
+    let prover = ProverClient::builder().cpu().build();
+    // Set up the key from the ELF itself, a cached key is only as good as the
+    // cache directory
+    let (_, vk) = prover.setup(GAME_ELF);
+    if proof.vk.bytes32() != vk.bytes32() {
+        fail("Proof was made for a different game program");
+    }
+
+    prover
+        .verify(&proof.proof, &proof.vk)
+        .unwrap_or_else(|_| fail("Proof verification failed"));
+    println!("Proof verified for vkey {}.", vk.bytes32());
+}
@@ -0,0 +1,201 @@
+//! Register agents and submit games against a `ConnectXGame` deployment.
+//!
+//! Deployment flags fall back to the environment, see `ZeromindConfig::from_env`.
+//! For example, against a local Anvil node:
+//! ```shell
+//! cargo run --release --bin zeromind -- --anvil --contract 0x... submit
+//! ```
+
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use clap::{Parser, Subcommand};
+use game_lib::reducer::reducer;
+use game_utils::print::print_public_state;
+use sp1_sdk::{include_elf, HashableKey, ProverClient};
+use turbo_sp1::key_cache::KeyCache;
+use turbo_sp1::zeromind::{
+    zeromind_submit_agent_with_options, SignerSource, SubmitMode, SubmitOptions,
+    ZeromindAgentSubmission, ZeromindConfig,
+};
+
+/// The ELF (executable and linkable format) file for the Succinct RISC-V zkVM.
+pub const GAME_ELF: &[u8] = include_elf!("game-program");
+pub const AGENT_RANDOM_ELF: &[u8] = include_elf!("agent-random");
+pub const AGENT_MINIMAX_ELF: &[u8] = include_elf!("agent-minimax");
+
+/// Keystore password, kept out of `Debug` output.
+#[derive(Clone)]
+struct Password(String);
+
+impl From<String> for Password {
+    fn from(password: String) -> Self {
+        Self(password)
+    }
+}
+
+impl fmt::Debug for Password {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<redacted>")
+    }
+}
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// Target a local Anvil node, signing with its first account.
+    #[arg(long)]
+    anvil: bool,
+
+    /// Address of the `ConnectXGame` contract.
+    #[arg(long)]
+    contract: Option<String>,
+
+    #[arg(long)]
+    chain_id: Option<u64>,
+
+    #[arg(long)]
+    rpc_url: Option<String>,
+
+    /// Sign with an encrypted JSON keystore.
+    #[arg(long)]
+    keystore: Option<PathBuf>,
+
+    #[arg(
+        long,
+        env = "ZEROMIND_KEYSTORE_PASSWORD",
+        default_value = "",
+        hide_env_values = true
+    )]
+    keystore_password: Password,
+
+    #[arg(long)]
+    gas_limit: Option<u64>,
+
+    #[arg(long)]
+    max_fee_per_gas: Option<u128>,
+
+    #[arg(long)]
+    max_priority_fee_per_gas: Option<u128>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Register the agent program at `elf` under `name`.
+    Register {
+        #[arg(long)]
+        elf: PathBuf,
+        #[arg(long)]
+        name: String,
+    },
+    /// Play Minimax against Random and submit the game.
+    Submit {
+        /// Stop after executing and cross checking the programs.
+        #[arg(long)]
+        check_only: bool,
+        /// Write the proofs and calldata here instead of sending transactions.
+        #[arg(long, conflicts_with = "check_only")]
+        dry_run: Option<PathBuf>,
+        /// Prover calls running at once.
+        #[arg(long, default_value_t = 3)]
+        max_parallel: usize,
+    },
+}
+
+impl Args {
+    fn config(&self) -> Result<ZeromindConfig, String> {
+        let mut config = ZeromindConfig::from_env()?;
+
+        if self.anvil {
+            config = ZeromindConfig::anvil(config.contract);
+        }
+        if let Some(contract) = &self.contract {
+            config.contract = contract.parse().map_err(|_| "Invalid contract address")?;
+        }
+        if let Some(chain_id) = self.chain_id {
+            config.chain_id = chain_id;
+        }
+        if let Some(rpc_url) = &self.rpc_url {
+            config.rpc_url = rpc_url.clone();
+        }
+        if let Some(path) = &self.keystore {
+            config.signer = SignerSource::Keystore {
+                path: path.clone(),
+                password: self.keystore_password.0.clone(),
+            };
+        }
+        config.gas.gas_limit = self.gas_limit.or(config.gas.gas_limit);
+        config.gas.max_fee_per_gas = self.max_fee_per_gas.or(config.gas.max_fee_per_gas);
+        config.gas.max_priority_fee_per_gas = self
+            .max_priority_fee_per_gas
+            .or(config.gas.max_priority_fee_per_gas);
+
+        Ok(config)
+    }
+}
+
+async fn run(args: Args) -> Result<(), String> {
+    let config = args.config()?;
+    let client = Arc::new(ProverClient::from_env());
+
+    match args.command {
+        Command::Register { elf, name } => {
+            let elf = std::fs::read(&elf).map_err(|e| format!("Failed to read ELF: {}", e))?;
+            let keys = KeyCache::global().get_or_setup(&elf, || client.setup(&elf))?;
+
+            config
+                .connect()
+                .await?
+                .register_agent(&keys.1, &name)
+                .await?;
+            println!("Registered {} with vkey {}", name, keys.1.bytes32());
+        }
+        Command::Submit {
+            check_only,
+            dry_run,
+            max_parallel,
+        } => {
+            let mode = match dry_run {
+                Some(dir) => SubmitMode::DryRun { dir },
+                None if check_only => SubmitMode::CheckOnly,
+                None => SubmitMode::OnChain,
+            };
+
+            let public_state = zeromind_submit_agent_with_options(
+                client,
+                &config,
+                reducer,
+                Arc::new(GAME_ELF.to_vec()),
+                ZeromindAgentSubmission::new(agent_minimax::agent, AGENT_MINIMAX_ELF, "Minimax"),
+                ZeromindAgentSubmission::new(agent_random::agent, AGENT_RANDOM_ELF, "Random"),
+                SubmitOptions {
+                    mode,
+                    max_parallel,
+                    ..Default::default()
+                },
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+
+            print_public_state(&public_state);
+            println!("Moves: {:?}", public_state.moves);
+        }
+    }
+
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() {
+    sp1_sdk::utils::setup_logger();
+    dotenv::dotenv().ok();
+
+    if let Err(e) = run(Args::parse()).await {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,280 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+use futures_util::{SinkExt, StreamExt};
+use serde_json::Value;
+use tokio::net::TcpStream;
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+use turbo_protocol::{
+    ClientEnvelope, ClientMessage, ProofStatus, ProofType, ProtocolError, ServerEnvelope,
+    ServerMessage, PROTOCOL_VERSION,
+};
+
+#[derive(Debug)]
+pub enum ClientError {
+    /// The connection failed or a frame could not be sent or read.
+    Transport(String),
+    /// The server answered with an error.
+    Protocol(ProtocolError),
+    /// The server answered with a message of the wrong type.
+    UnexpectedReply(Box<ServerMessage>),
+    Closed,
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Transport(e) => write!(f, "transport error: {}", e),
+            ClientError::Protocol(e) => write!(f, "server error: {}", e),
+            ClientError::UnexpectedReply(reply) => write!(f, "unexpected reply: {:?}", reply),
+            ClientError::Closed => write!(f, "connection closed"),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+/// Reply to `join_session`.
+#[derive(Debug, Clone)]
+pub struct JoinedSession {
+    pub session_id: String,
+    pub player_idx: usize,
+    pub server_commitment: String,
+    pub seed_commitment: String,
+}
+
+/// WebSocket client for the turbo-sp1 server.
+pub struct TurboClient {
+    socket: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    next_id: u64,
+    /// Message to sign for `authenticate`.
+    challenge: String,
+    /// Server pushes received while waiting for a reply.
+    events: VecDeque<ServerMessage>,
+}
+
+impl TurboClient {
+    /// Connect to `url` (e.g. `ws://localhost:3030/ws`) and perform the
+    /// version handshake.
+    pub async fn connect(url: &str) -> Result<Self, ClientError> {
+        let (socket, _) = connect_async(url)
+            .await
+            .map_err(|e| ClientError::Transport(e.to_string()))?;
+
+        let mut client = Self {
+            socket,
+            next_id: 0,
+            challenge: String::new(),
+            events: VecDeque::new(),
+        };
+        match client
+            .request(ClientMessage::Hello {
+                version: PROTOCOL_VERSION,
+            })
+            .await?
+        {
+            ServerMessage::Welcome { challenge, .. } => {
+                client.challenge = challenge;
+                Ok(client)
+            }
+            reply => Err(ClientError::UnexpectedReply(Box::new(reply))),
+        }
+    }
+
+    /// Send `message` and wait for the reply carrying the same request id.
+    pub async fn request(&mut self, message: ClientMessage) -> Result<ServerMessage, ClientError> {
+        self.next_id += 1;
+        let id = self.next_id;
+
+        let text = serde_json::to_string(&ClientEnvelope {
+            id: Some(id),
+            message,
+        })
+        .map_err(|e| ClientError::Transport(e.to_string()))?;
+        self.socket
+            .send(Message::Text(text))
+            .await
+            .map_err(|e| ClientError::Transport(e.to_string()))?;
+
+        loop {
+            let envelope = self.next_envelope().await?;
+            match envelope.id {
+                Some(reply_id) if reply_id == id => {
+                    return match envelope.message {
+                        ServerMessage::Error { error } => Err(ClientError::Protocol(error)),
+                        message => Ok(message),
+                    };
+                }
+                Some(_) => continue,
+                None => self.events.push_back(envelope.message),
+            }
+        }
+    }
+
+    /// Wait for the next message pushed by the server, such as the state
+    /// after another player's action or a proof status change.
+    pub async fn next_event(&mut self) -> Result<ServerMessage, ClientError> {
+        if let Some(event) = self.events.pop_front() {
+            return Ok(event);
+        }
+
+        loop {
+            let envelope = self.next_envelope().await?;
+            if envelope.id.is_none() {
+                return Ok(envelope.message);
+            }
+        }
+    }
+
+    async fn next_envelope(&mut self) -> Result<ServerEnvelope, ClientError> {
+        while let Some(frame) = self.socket.next().await {
+            let text = match frame.map_err(|e| ClientError::Transport(e.to_string()))? {
+                Message::Text(text) => text,
+                Message::Close(_) => return Err(ClientError::Closed),
+                _ => continue,
+            };
+
+            return serde_json::from_str(&text).map_err(|e| ClientError::Transport(e.to_string()));
+        }
+
+        Err(ClientError::Closed)
+    }
+
+    /// Challenge sent by the server, to sign with `personal_sign` (EIP-191)
+    /// using the key of the address passed to `authenticate`.
+    pub fn challenge(&self) -> &str {
+        &self.challenge
+    }
+
+    /// Bind the connection to `address` with a hex encoded signature of
+    /// `challenge()`. Required before joining a session.
+    pub async fn authenticate(
+        &mut self,
+        address: &str,
+        signature: &str,
+    ) -> Result<String, ClientError> {
+        match self
+            .request(ClientMessage::Authenticate {
+                address: address.to_string(),
+                signature: signature.to_string(),
+            })
+            .await?
+        {
+            ServerMessage::Authenticated { address } => Ok(address),
+            reply => Err(ClientError::UnexpectedReply(Box::new(reply))),
+        }
+    }
+
+    /// Join `session_id`, or create a session from `game_config` when `None`.
+    /// `seed` is a hex encoded BN254 G1 point adding the player's entropy.
+    pub async fn join_session(
+        &mut self,
+        session_id: Option<String>,
+        game_config: Option<Vec<u8>>,
+        seed: Option<String>,
+    ) -> Result<JoinedSession, ClientError> {
+        match self
+            .request(ClientMessage::JoinSession {
+                session_id,
+                game_config,
+                seed,
+            })
+            .await?
+        {
+            ServerMessage::Joined {
+                session_id,
+                player_idx,
+                server_commitment,
+                seed_commitment,
+            } => Ok(JoinedSession {
+                session_id,
+                player_idx,
+                server_commitment,
+                seed_commitment,
+            }),
+            reply => Err(ClientError::UnexpectedReply(Box::new(reply))),
+        }
+    }
+
+    /// Watch `session_id` without taking a seat, returning its public state.
+    /// Later changes arrive through `next_event`.
+    pub async fn spectate(&mut self, session_id: String) -> Result<Value, ClientError> {
+        match self.request(ClientMessage::Spectate { session_id }).await? {
+            ServerMessage::Spectating { public_state, .. } => Ok(public_state),
+            reply => Err(ClientError::UnexpectedReply(Box::new(reply))),
+        }
+    }
+
+    /// Dispatch actions, returning the public state and the client response.
+    pub async fn send_actions(&mut self, actions: Value) -> Result<(Value, Value), ClientError> {
+        match self.request(ClientMessage::Action { actions }).await? {
+            ServerMessage::State {
+                public_state,
+                client_response,
+            } => Ok((public_state, client_response)),
+            reply => Err(ClientError::UnexpectedReply(Box::new(reply))),
+        }
+    }
+
+    /// Queue a proof of the session, returning its id. `backend` overrides the
+    /// prover backend the server would pick.
+    pub async fn request_proof(
+        &mut self,
+        proof_type: ProofType,
+        backend: Option<String>,
+    ) -> Result<String, ClientError> {
+        match self
+            .request(ClientMessage::Proof {
+                proof_type,
+                backend,
+            })
+            .await?
+        {
+            ServerMessage::ProofQueued { proof_id } => Ok(proof_id),
+            reply => Err(ClientError::UnexpectedReply(Box::new(reply))),
+        }
+    }
+
+    pub async fn proof_status(
+        &mut self,
+        proof_id: Option<String>,
+    ) -> Result<ProofStatus, ClientError> {
+        match self
+            .request(ClientMessage::ProofStatus { proof_id })
+            .await?
+        {
+            ServerMessage::ProofStatus { status, .. } => Ok(status),
+            reply => Err(ClientError::UnexpectedReply(Box::new(reply))),
+        }
+    }
+
+    /// Take back the last action, returning the public state and the client
+    /// response after it.
+    pub async fn undo(&mut self) -> Result<(Value, Value), ClientError> {
+        match self.request(ClientMessage::Undo).await? {
+            ServerMessage::State {
+                public_state,
+                client_response,
+            } => Ok((public_state, client_response)),
+            reply => Err(ClientError::UnexpectedReply(Box::new(reply))),
+        }
+    }
+
+    pub async fn reveal(&mut self) -> Result<Value, ClientError> {
+        match self.request(ClientMessage::Reveal).await? {
+            ServerMessage::Revealed { reveal } => Ok(reveal),
+            reply => Err(ClientError::UnexpectedReply(Box::new(reply))),
+        }
+    }
+
+    /// Leave the session, which is closed once every player has left or the
+    /// game is over.
+    pub async fn close_session(&mut self) -> Result<String, ClientError> {
+        match self.request(ClientMessage::CloseSession).await? {
+            ServerMessage::Closed { session_id } | ServerMessage::Left { session_id } => {
+                Ok(session_id)
+            }
+            reply => Err(ClientError::UnexpectedReply(Box::new(reply))),
+        }
+    }
+}
@@ -0,0 +1,80 @@
+//! Length-delimited action stream shared by agent proofs, `HasActions` and the
+//! contracts. Every action is a big-endian `u16` length followed by its
+//! serialized bytes, so actions of any size can be carried.
+
+pub fn push_action(stream: &mut Vec<u8>, action: &[u8]) {
+    let len = u16::try_from(action.len()).expect("Action too large");
+    stream.extend_from_slice(&len.to_be_bytes());
+    stream.extend_from_slice(action);
+}
+
+pub fn encode_actions<'a, I>(actions: I) -> Vec<u8>
+where
+    I: IntoIterator<Item = &'a [u8]>,
+{
+    let mut stream = Vec::new();
+    for action in actions {
+        push_action(&mut stream, action);
+    }
+    stream
+}
+
+/// Iterator over the serialized actions of a stream.
+pub struct ActionStream<'a> {
+    remaining: &'a [u8],
+}
+
+impl<'a> ActionStream<'a> {
+    pub fn new(stream: &'a [u8]) -> Self {
+        Self { remaining: stream }
+    }
+}
+
+impl<'a> Iterator for ActionStream<'a> {
+    type Item = Result<&'a [u8], &'static str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining.is_empty() {
+            return None;
+        }
+
+        if self.remaining.len() < 2 {
+            self.remaining = &[];
+            return Some(Err("Truncated action length"));
+        }
+
+        let len = u16::from_be_bytes([self.remaining[0], self.remaining[1]]) as usize;
+        if self.remaining.len() < 2 + len {
+            self.remaining = &[];
+            return Some(Err("Truncated action"));
+        }
+
+        let action = &self.remaining[2..2 + len];
+        self.remaining = &self.remaining[2 + len..];
+        Some(Ok(action))
+    }
+}
+
+pub fn decode_actions(stream: &[u8]) -> Result<Vec<&[u8]>, &'static str> {
+    ActionStream::new(stream).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let actions: Vec<&[u8]> = vec![&[3], &[], &[1, 2, 3, 4]];
+        let stream = encode_actions(actions.iter().copied());
+
+        assert_eq!(stream, vec![0, 1, 3, 0, 0, 0, 4, 1, 2, 3, 4]);
+        assert_eq!(decode_actions(&stream).unwrap(), actions);
+    }
+
+    #[test]
+    fn truncated() {
+        assert!(decode_actions(&[0]).is_err());
+        assert!(decode_actions(&[0, 2, 1]).is_err());
+    }
+}
@@ -1,5 +1,7 @@
 use serde_json::Value;
 
+#[cfg(not(target_os = "zkvm"))]
+use crate::rand::provably_fair::RandDraw;
 use crate::{
     crypto::fnv::FnvHasher,
     metadata::{PlayerMetadata, ServerMetadata},
@@ -12,6 +14,10 @@ pub struct TurboActionContextInner {
     action_hash: FnvHasher,
     rand: BnRandomizer,
     pub client_response: Option<Value>,
+
+    // Draws are only recorded on the host so they can be audited after the reveal
+    #[cfg(not(target_os = "zkvm"))]
+    draws: Vec<RandDraw>,
 }
 
 impl TurboActionContextInner {
@@ -28,6 +34,9 @@ impl TurboActionContextInner {
             ]),
             action_hash: FnvHasher::new(),
             client_response: None,
+
+            #[cfg(not(target_os = "zkvm"))]
+            draws: Vec::new(),
         };
 
         let current_bytes =
@@ -42,11 +51,21 @@ impl TurboActionContextInner {
     }
 
     pub fn rand_u32(&mut self) -> u32 {
-        self.rand.next_u32()
+        let value = self.rand.next_u32();
+
+        #[cfg(not(target_os = "zkvm"))]
+        self.draws.push(RandDraw::U32(value));
+
+        value
     }
 
     pub fn rand_u64(&mut self) -> u64 {
-        self.rand.next_u64()
+        let value = self.rand.next_u64();
+
+        #[cfg(not(target_os = "zkvm"))]
+        self.draws.push(RandDraw::U64(value));
+
+        value
     }
 
     pub fn action_hash(&self) -> [u32; 8] {
@@ -56,6 +75,11 @@ impl TurboActionContextInner {
     pub fn update_action_hash(&mut self, action: &[u8]) {
         self.action_hash.next(action);
     }
+
+    #[cfg(not(target_os = "zkvm"))]
+    pub fn draws(&self) -> &[RandDraw] {
+        &self.draws
+    }
 }
 
 pub struct TurboActionContext<'a> {
@@ -33,10 +33,16 @@ pub fn bn254_export_affine_g1_memcpy(affine: &AffineG1) -> [u32; 16] {
 }
 
 pub fn bn254_import_affine_g1(bytes: &[u8; 64]) -> AffineG1 {
-    let x = Fq::from_slice(&bytes[0..32]).unwrap();
-    let y = Fq::from_slice(&bytes[32..64]).unwrap();
+    bn254_try_import_affine_g1(bytes).unwrap()
+}
+
+/// Same as `bn254_import_affine_g1` for untrusted input: fails if a coordinate
+/// is not a field element or the point is not on the curve.
+pub fn bn254_try_import_affine_g1(bytes: &[u8; 64]) -> Result<AffineG1, &'static str> {
+    let x = Fq::from_slice(&bytes[0..32]).map_err(|_| "Invalid x coordinate")?;
+    let y = Fq::from_slice(&bytes[32..64]).map_err(|_| "Invalid y coordinate")?;
 
-    AffineG1::new(x, y).unwrap()
+    AffineG1::new(x, y).map_err(|_| "Point is not on the curve")
 }
 
 pub fn bn254_import_affine_g1_memcpy(data: &[u32; 16]) -> AffineG1 {
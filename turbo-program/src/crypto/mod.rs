@@ -1,3 +1,4 @@
 pub mod bn_math;
 pub mod bn_serialize;
 pub mod fnv;
+pub mod keccak;
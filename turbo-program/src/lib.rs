@@ -1,7 +1,9 @@
+pub mod action_stream;
 pub mod context;
 pub mod crypto;
 pub mod metadata;
 pub mod program;
+pub mod public_values;
 pub mod rand;
 pub mod traits;
 pub mod zeromind;
@@ -1,11 +1,35 @@
 use serde::{Deserialize, Serialize};
 
+use crate::crypto::keccak::{keccak256, words_to_bytes};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerMetadata {
     pub random_seed: [u32; 16],
+    pub match_nonce: [u8; 32],
+    pub game_config: Vec<u8>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlayerMetadata {
     pub random_seed: [u32; 16],
+    /// Ethereum address that owns the seat, all zeroes for an anonymous seat.
+    #[serde(default)]
+    pub address: [u8; 20],
+}
+
+/// Keccak256 of the seed point words in little-endian order.
+pub fn seed_hash(seed: &[u32; 16]) -> [u8; 32] {
+    keccak256(&words_to_bytes(seed))
+}
+
+impl ServerMetadata {
+    pub fn seed_hash(&self) -> [u8; 32] {
+        seed_hash(&self.random_seed)
+    }
+}
+
+impl PlayerMetadata {
+    pub fn seed_hash(&self) -> [u8; 32] {
+        seed_hash(&self.random_seed)
+    }
 }
@@ -3,36 +3,66 @@ use alloy_sol_types::SolValue;
 use crate::{
     context::{TurboActionContext, TurboActionContextInner},
     metadata::{PlayerMetadata, ServerMetadata},
-    traits::TurboActionSerialization,
+    public_values::TurboPublicValues,
+    rand::provably_fair::check_seeds,
+    traits::{TurboActionSerialization, TurboInitState},
 };
 
-pub type TurboReducer<PublicState, PrivateState, GameAction> = fn(
-    public_state: &mut PublicState,
-    private_state: &mut PrivateState,
-    action: &GameAction,
-    context: &mut TurboActionContext,
-);
+/// Applies an action to the game state. A rejected action must return its
+/// error before touching the state.
+pub type TurboReducer<PublicState, PrivateState, GameAction> =
+    fn(
+        public_state: &mut PublicState,
+        private_state: &mut PrivateState,
+        action: &GameAction,
+        context: &mut TurboActionContext,
+    ) -> Result<(), <GameAction as TurboActionSerialization>::ReducerError>;
+
+/// What the game program does with an action the reducer rejects.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InvalidActionPolicy {
+    /// Panic, so no proof can be made of an action log with invalid actions.
+    #[default]
+    Abort,
+    /// Ignore the action. It still counts towards the action hash and the
+    /// match digest.
+    Skip,
+}
 
 /*
 Stdin Format:
 - Server Metadata
     - Server Random Seed
+    - Match Nonce
+    - Game Config
 - Players Metadata
     - Client Seed
 - Actions
+
+Public Values Format (ABI encoded `TurboPublicValues`):
+- Match Nonce
+- Match Digest
+- Server Seed Hash
+- Player Seed Hashes
+- Player Action Hashes
+- Private State Hash
+- Public State
 */
 
 fn turbo_program_inner<PublicState, PrivateState, GameAction>(
     reducer: TurboReducer<PublicState, PrivateState, GameAction>,
+    policy: InvalidActionPolicy,
+    game_config: &[u8],
     action_raw: &[u8],
     contexts: &mut [&mut TurboActionContext],
-) -> Vec<u8>
+) -> (Vec<u8>, Vec<u8>)
 where
-    PublicState: Default + SolValue,
-    PrivateState: Default,
+    PublicState: TurboInitState + SolValue,
+    PrivateState: Default + SolValue,
     GameAction: TurboActionSerialization,
 {
-    let mut public_state = PublicState::default();
+    let mut public_state =
+        PublicState::init_state(game_config).expect("Failed to initialize game config");
     let mut private_state = PrivateState::default();
     let mut remaining_actions = action_raw;
 
@@ -53,33 +83,80 @@ where
         );
 
         // Process the action
-        reducer(&mut public_state, &mut private_state, &action, context);
+        if let Err(e) = reducer(&mut public_state, &mut private_state, &action, context) {
+            if policy == InvalidActionPolicy::Abort {
+                panic!("Invalid action: {}", Into::<&'static str>::into(e));
+            }
+        }
 
         // Move to next action
         remaining_actions = next_actions;
     }
 
-    PublicState::abi_encode(&public_state)
+    (
+        PublicState::abi_encode(&public_state),
+        PrivateState::abi_encode(&private_state),
+    )
 }
 
+/// Game program entrypoint, aborting on invalid actions.
 pub fn turbo_program<PublicState, PrivateState, GameAction>(
     reducer: TurboReducer<PublicState, PrivateState, GameAction>,
 ) where
-    PublicState: Default + SolValue,
-    PrivateState: Default,
+    PublicState: TurboInitState + SolValue,
+    PrivateState: Default + SolValue,
+    GameAction: TurboActionSerialization,
+{
+    turbo_program_with_policy(reducer, InvalidActionPolicy::Abort)
+}
+
+pub fn turbo_program_with_policy<PublicState, PrivateState, GameAction>(
+    reducer: TurboReducer<PublicState, PrivateState, GameAction>,
+    policy: InvalidActionPolicy,
+) where
+    PublicState: TurboInitState + SolValue,
+    PrivateState: Default + SolValue,
     GameAction: TurboActionSerialization,
 {
     let server_metadata = sp1_zkvm::io::read::<ServerMetadata>();
     let player_metadata = sp1_zkvm::io::read::<Vec<PlayerMetadata>>();
     let action_raw = sp1_zkvm::io::read::<Vec<u8>>();
 
+    let public_values = compute_public_values(
+        reducer,
+        policy,
+        &server_metadata,
+        &player_metadata,
+        &action_raw,
+    );
+    sp1_zkvm::io::commit_slice(&TurboPublicValues::abi_encode(&public_values));
+}
+
+/// Run a match and build the public values the game program commits. Also
+/// used on the host to check a proof against its inputs.
+pub fn compute_public_values<PublicState, PrivateState, GameAction>(
+    reducer: TurboReducer<PublicState, PrivateState, GameAction>,
+    policy: InvalidActionPolicy,
+    server_metadata: &ServerMetadata,
+    player_metadata: &[PlayerMetadata],
+    action_raw: &[u8],
+) -> TurboPublicValues
+where
+    PublicState: TurboInitState + SolValue,
+    PrivateState: Default + SolValue,
+    GameAction: TurboActionSerialization,
+{
+    // Seeds must be valid curve points, the committed server seed hash is the
+    // commitment the server published before players joined
+    check_seeds(server_metadata, player_metadata);
+
     // Create contexts for all players and set them
     let mut player_contexts = Vec::new();
     let mut context_refs = Vec::new();
 
     // First create all the contexts
     for (i, metadata) in player_metadata.iter().enumerate() {
-        player_contexts.push(TurboActionContext::new(&server_metadata, metadata, i));
+        player_contexts.push(TurboActionContext::new(server_metadata, metadata, i));
     }
 
     // Then collect mutable references to them
@@ -87,10 +164,27 @@ pub fn turbo_program<PublicState, PrivateState, GameAction>(
         context_refs.push(context);
     }
 
-    // Encode and commit the final public state
-    sp1_zkvm::io::commit_slice(&turbo_program_inner(
+    let (public_state, private_state) = turbo_program_inner(
         reducer,
-        &action_raw,
+        policy,
+        &server_metadata.game_config,
+        action_raw,
         &mut context_refs,
-    ));
+    );
+
+    let action_hashes: Vec<[u32; 8]> = context_refs
+        .iter()
+        .map(|context| context.action_hash())
+        .collect();
+
+    // Encode the final public state along with the seed commitments. The
+    // private state is only committed as a salted hash.
+    TurboPublicValues::new(
+        server_metadata,
+        player_metadata,
+        action_raw,
+        &action_hashes,
+        &private_state,
+        public_state,
+    )
 }
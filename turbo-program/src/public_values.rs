@@ -0,0 +1,119 @@
+use alloy_primitives::{Address, Bytes, B256};
+use alloy_sol_types::{sol, SolValue};
+
+use crate::{
+    crypto::keccak::{keccak256, words_to_bytes},
+    metadata::{PlayerMetadata, ServerMetadata},
+};
+
+sol! {
+    /// Public values committed by `turbo_program`.
+    ///
+    /// Besides the ABI encoded public state, the envelope binds the proof to the
+    /// seeds it was generated with, so a verifier can tell the server did not grind
+    /// seeds until it got an outcome it liked.
+    #[derive(Debug)]
+    struct TurboPublicValues {
+        bytes32 match_nonce;  // nonce picked by the server for this match
+        bytes32 match_digest;  // keccak256 of the `MatchTranscript`
+        bytes32 server_seed_hash;  // keccak256 of the server seed point
+        bytes32[] player_seed_hashes;  // keccak256 of each player seed point
+        address[] player_addresses;  // address owning each seat, zero if anonymous
+        bytes32[] action_hashes;  // final action hash of each player context
+        bytes32 private_state_hash;  // keccak256 of the server seed and ABI encoded private state
+        bytes public_state;  // ABI encoded public state
+    }
+
+    /// Transcript hashed into the match digest shared by game and agent proofs.
+    struct MatchTranscript {
+        bytes32 match_nonce;
+        bytes32 server_seed_hash;
+        bytes32[] player_seed_hashes;
+        address[] player_addresses;
+        bytes game_config;  // configuration the public state was initialized with
+        bytes actions;  // player tagged action stream as read by `turbo_program`
+    }
+
+    /// Public values committed by `zeromind_agent_program`.
+    #[derive(Debug)]
+    struct AgentPublicValues {
+        bytes32 match_digest;  // must match the digest of the game proof
+        uint8 player_index;  // seat the agent played
+        bytes actions;  // length-delimited action stream of the match
+    }
+}
+
+fn player_seed_hashes(player_metadata: &[PlayerMetadata]) -> Vec<B256> {
+    player_metadata
+        .iter()
+        .map(|metadata| B256::from(metadata.seed_hash()))
+        .collect()
+}
+
+fn player_addresses(player_metadata: &[PlayerMetadata]) -> Vec<Address> {
+    player_metadata
+        .iter()
+        .map(|metadata| Address::from(metadata.address))
+        .collect()
+}
+
+/// Digest of the match seeds, nonce, seat owners, game config and player tagged
+/// action stream.
+pub fn match_digest(
+    server_metadata: &ServerMetadata,
+    player_metadata: &[PlayerMetadata],
+    action_raw: &[u8],
+) -> B256 {
+    let transcript = MatchTranscript {
+        match_nonce: B256::from(server_metadata.match_nonce),
+        server_seed_hash: B256::from(server_metadata.seed_hash()),
+        player_seed_hashes: player_seed_hashes(player_metadata),
+        player_addresses: player_addresses(player_metadata),
+        game_config: Bytes::copy_from_slice(&server_metadata.game_config),
+        actions: Bytes::copy_from_slice(action_raw),
+    };
+    B256::from(keccak256(&MatchTranscript::abi_encode(&transcript)))
+}
+
+/// Hash of the ABI encoded private state, salted with the server seed so hidden
+/// data can't be guessed from the hash before the seed is revealed.
+pub fn private_state_hash(server_metadata: &ServerMetadata, private_state: &[u8]) -> B256 {
+    let mut data = words_to_bytes(&server_metadata.random_seed);
+    data.extend_from_slice(private_state);
+    B256::from(keccak256(&data))
+}
+
+impl TurboPublicValues {
+    pub fn new(
+        server_metadata: &ServerMetadata,
+        player_metadata: &[PlayerMetadata],
+        action_raw: &[u8],
+        action_hashes: &[[u32; 8]],
+        private_state: &[u8],
+        public_state: Vec<u8>,
+    ) -> Self {
+        Self {
+            match_nonce: B256::from(server_metadata.match_nonce),
+            match_digest: match_digest(server_metadata, player_metadata, action_raw),
+            server_seed_hash: B256::from(server_metadata.seed_hash()),
+            player_seed_hashes: player_seed_hashes(player_metadata),
+            player_addresses: player_addresses(player_metadata),
+            action_hashes: action_hashes
+                .iter()
+                .map(|hash| B256::from_slice(&words_to_bytes(hash)))
+                .collect(),
+            private_state_hash: private_state_hash(server_metadata, private_state),
+            public_state: Bytes::from(public_state),
+        }
+    }
+}
+
+impl AgentPublicValues {
+    pub fn new(match_digest: B256, player_index: u8, actions: &[u8]) -> Self {
+        Self {
+            match_digest,
+            player_index,
+            actions: Bytes::copy_from_slice(actions),
+        }
+    }
+}
@@ -1 +1,158 @@
+//! Commit–reveal protocol for the seeds feeding `BnRandomizer`.
+//!
+//! 1. The server picks its BN254 seed point and publishes `commit_seed(seed)`
+//!    before any player joins.
+//! 2. Players join with their own seed points, so neither side controls the
+//!    combined seed alone. Each player gets `commit_combined_seed` back, tying
+//!    the server commitment to the seed the server recorded for them.
+//! 3. Once the game is over the server reveals its seed and anyone can recompute
+//!    every draw with `FairnessReveal::verify`.
 
+use serde::{Deserialize, Serialize};
+use substrate_bn::AffineG1;
+
+use crate::{
+    crypto::{bn_serialize::bn254_import_affine_g1_memcpy, keccak::keccak256},
+    metadata::{seed_hash, PlayerMetadata, ServerMetadata},
+    rand::bn_randomizer::BnRandomizer,
+};
+
+/// A value drawn by a player context, in the order the reducer requested it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RandDraw {
+    U32(u32),
+    U64(u64),
+}
+
+/// Commitment the server publishes before players join.
+pub fn commit_seed(seed: &[u32; 16]) -> [u8; 32] {
+    seed_hash(seed)
+}
+
+/// Commitment to the seed a player context combines from the server seed and
+/// `player_seed`, without revealing the server seed.
+pub fn commit_combined_seed(server_commitment: &[u8; 32], player_seed: &[u32; 16]) -> [u8; 32] {
+    let mut data = server_commitment.to_vec();
+    data.extend_from_slice(&seed_hash(player_seed));
+    keccak256(&data)
+}
+
+pub fn verify_commitment(commitment: &[u8; 32], seed: &[u32; 16]) -> bool {
+    commit_seed(seed) == *commitment
+}
+
+/// Check that the seed words hold an affine BN254 G1 point.
+pub fn is_valid_seed(seed: &[u32; 16]) -> bool {
+    let point = bn254_import_affine_g1_memcpy(seed);
+    AffineG1::new(point.x(), point.y()).is_ok()
+}
+
+/// Panics if the server or any player seed is not a point on the curve.
+pub fn check_seeds(server_metadata: &ServerMetadata, player_metadata: &[PlayerMetadata]) {
+    if !is_valid_seed(&server_metadata.random_seed) {
+        panic!("Invalid server seed");
+    }
+
+    for metadata in player_metadata {
+        if !is_valid_seed(&metadata.random_seed) {
+            panic!("Invalid player seed");
+        }
+    }
+}
+
+/// Recompute the draws of a single player context from the revealed seeds.
+pub fn verify_draws(
+    server_seed: &[u32; 16],
+    player_seed: &[u32; 16],
+    draws: &[RandDraw],
+) -> Result<(), &'static str> {
+    let mut rand = BnRandomizer::new_with_seeds(vec![*server_seed, *player_seed]);
+
+    for draw in draws {
+        let matches = match draw {
+            RandDraw::U32(value) => rand.next_u32() == *value,
+            RandDraw::U64(value) => rand.next_u64() == *value,
+        };
+
+        if !matches {
+            return Err("Draw does not match the revealed seeds");
+        }
+    }
+
+    Ok(())
+}
+
+/// Everything a player needs to audit the randomness of a finished session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FairnessReveal {
+    pub server_commitment: [u8; 32],
+    pub server_seed: [u32; 16],
+    pub player_seeds: Vec<[u32; 16]>,
+    pub draws: Vec<Vec<RandDraw>>,
+}
+
+impl FairnessReveal {
+    pub fn verify(&self) -> Result<(), &'static str> {
+        if !verify_commitment(&self.server_commitment, &self.server_seed) {
+            return Err("Server seed does not match its commitment");
+        }
+
+        if !is_valid_seed(&self.server_seed) {
+            return Err("Invalid server seed");
+        }
+
+        if self.player_seeds.len() != self.draws.len() {
+            return Err("Draws do not match the number of players");
+        }
+
+        for (player_seed, draws) in self.player_seeds.iter().zip(self.draws.iter()) {
+            if !is_valid_seed(player_seed) {
+                return Err("Invalid player seed");
+            }
+            verify_draws(&self.server_seed, player_seed, draws)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::bn_serialize::bn254_export_affine_g1_memcpy;
+    use substrate_bn::Fr;
+
+    fn seed(scalar: u64) -> [u32; 16] {
+        let point = AffineG1::one() * Fr::from_str(&scalar.to_string()).unwrap();
+        bn254_export_affine_g1_memcpy(&point)
+    }
+
+    #[test]
+    fn reveal_roundtrip() {
+        let server_seed = seed(7);
+        let player_seed = seed(11);
+
+        let mut rand = BnRandomizer::new_with_seeds(vec![server_seed, player_seed]);
+        let draws = vec![
+            RandDraw::U32(rand.next_u32()),
+            RandDraw::U64(rand.next_u64()),
+            RandDraw::U32(rand.next_u32()),
+        ];
+
+        let reveal = FairnessReveal {
+            server_commitment: commit_seed(&server_seed),
+            server_seed,
+            player_seeds: vec![player_seed],
+            draws: vec![draws],
+        };
+        assert!(reveal.verify().is_ok());
+
+        let mut tampered = reveal.clone();
+        tampered.draws[0][1] = RandDraw::U64(0);
+        assert!(tampered.verify().is_err());
+
+        let mut wrong_seed = reveal;
+        wrong_seed.server_seed = seed(8);
+        assert!(wrong_seed.verify().is_err());
+    }
+}
@@ -1,12 +1,57 @@
 pub trait TurboActionSerialization: Sized {
+    /// Error the reducer returns when it rejects an action, e.g. a move out of
+    /// turn. The message is reported to the player.
+    type ReducerError: Into<&'static str>;
+
     fn deserialize(action: &[u8]) -> Result<(Self, &[u8]), &'static str>;
+    fn serialize(&self) -> Vec<u8>;
     fn serialize_json(json_str: &str) -> Result<Vec<u8>, &'static str>;
 }
 
+pub trait TurboInitState: Sized {
+    /// Build the initial state from the game config in `ServerMetadata`.
+    /// An empty config must give the game's default setup.
+    fn init_state(config: &[u8]) -> Result<Self, &'static str>;
+
+    /// Number of seats the game gives turns to, `None` if any number of
+    /// players can join.
+    fn max_players(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Projection of the game state sent to a single player, for games with
+/// hidden information.
+pub trait PlayerView<PrivateState> {
+    /// What `player_idx` is allowed to see. Anything taken from `private_state`
+    /// must belong to that player.
+    fn player_view(&self, private_state: &PrivateState, player_idx: usize) -> serde_json::Value;
+
+    /// What spectators and replays are allowed to see.
+    fn spectator_view(&self, private_state: &PrivateState) -> serde_json::Value;
+}
+
+/// Game rules for taking back actions.
+pub trait UndoRules {
+    /// Whether `player_idx` may take back its last action in this state.
+    fn can_undo(&self, player_idx: usize) -> bool;
+}
+
 pub trait HasTerminalState {
     fn is_terminal(&self) -> bool;
 }
 
 pub trait HasActions {
+    /// Actions played so far as a length-delimited `action_stream`.
     fn actions(&self) -> Vec<u8>;
 }
+
+pub trait HasWinner {
+    /// Index of the winning player once the game is over, `None` for a draw.
+    fn winner(&self) -> Option<usize>;
+}
+
+pub trait HasCurrentPlayer {
+    /// Index of the player expected to act next, `None` once nobody is.
+    fn current_player(&self) -> Option<usize>;
+}
@@ -1,18 +1,24 @@
+use alloy_sol_types::SolValue;
+
 use crate::{
+    action_stream::ActionStream,
     context::TurboActionContext,
     metadata::{PlayerMetadata, ServerMetadata},
     program::TurboReducer,
-    traits::TurboActionSerialization,
+    public_values::{match_digest, AgentPublicValues},
+    traits::{HasCurrentPlayer, TurboActionSerialization, TurboInitState},
 };
 
-pub type ZeroMindAgent<PublicState> = fn(&PublicState, &mut TurboActionContext) -> u8;
+pub type ZeroMindAgent<PublicState, GameAction> =
+    fn(&PublicState, &mut TurboActionContext) -> GameAction;
 
-// Currently limited to 2 players turn based games
+// Turn order is taken from the public state, so games with any number of seats,
+// extra turns or passes are supported
 pub fn zeromind_agent_program<PublicState, PrivateState, GameAction>(
     reducer: TurboReducer<PublicState, PrivateState, GameAction>,
-    agent: ZeroMindAgent<PublicState>,
+    agent: ZeroMindAgent<PublicState, GameAction>,
 ) where
-    PublicState: Default,
+    PublicState: TurboInitState + HasCurrentPlayer,
     PrivateState: Default,
     GameAction: TurboActionSerialization,
 {
@@ -21,13 +27,13 @@ pub fn zeromind_agent_program<PublicState, PrivateState, GameAction>(
     let actions = sp1_zkvm::io::read::<Vec<u8>>();
     let player_id = sp1_zkvm::io::read::<u8>();
 
-    if player_id != 0 && player_id != 1 {
+    if player_id as usize >= player_metadata.len() {
         panic!("Invalid player id");
     }
 
-    let mut public_state = PublicState::default();
+    let mut public_state = PublicState::init_state(&server_metadata.game_config)
+        .expect("Failed to initialize game config");
     let mut private_state = PrivateState::default();
-    let mut current_player = 0;
 
     // Create contexts for all players and set them
     let mut player_contexts = Vec::new();
@@ -43,33 +49,53 @@ pub fn zeromind_agent_program<PublicState, PrivateState, GameAction>(
         context_refs.push(context);
     }
 
-    let actions_clone = actions.clone();
+    // Player tagged action stream, as the game program reads it
+    let mut action_raw = Vec::with_capacity(actions.len());
+
+    // Iterate over the length-delimited actions and apply them
+    for action in ActionStream::new(&actions) {
+        let action = action.expect("Failed to read action stream");
+        let Some(current_player) = public_state
+            .current_player()
+            .filter(|&current_player| current_player < context_refs.len())
+        else {
+            panic!("Invalid current player");
+        };
 
-    // Iterate over the actions and apply them
-    for action in actions {
-        let context = &mut context_refs[current_player as usize];
+        let context = &mut context_refs[current_player];
 
-        if current_player == player_id {
+        if current_player == player_id as usize {
             let real_action = agent(&public_state, context);
-            if action != real_action {
+            if action != real_action.serialize().as_slice() {
                 panic!("Invalid action");
             }
         }
 
         // Process the action
-        let action_parsed = GameAction::deserialize(&[action])
-            .expect("Failed to deserialize action")
-            .0;
-        reducer(
+        let (action_parsed, rest) =
+            GameAction::deserialize(action).expect("Failed to deserialize action");
+        if !rest.is_empty() {
+            panic!("Trailing action bytes");
+        }
+
+        if let Err(e) = reducer(
             &mut public_state,
             &mut private_state,
             &action_parsed,
             context,
-        );
+        ) {
+            panic!("Invalid action: {}", Into::<&'static str>::into(e));
+        }
 
-        current_player = 1 - current_player;
+        action_raw.push(current_player as u8);
+        action_raw.extend_from_slice(action);
     }
 
-    // Return the actions result
-    sp1_zkvm::io::commit_slice(&actions_clone);
+    // Bind the actions to the match seeds and nonce so the proof can't be replayed
+    let public_values = AgentPublicValues::new(
+        match_digest(&server_metadata, &player_metadata, &action_raw),
+        player_id,
+        &actions,
+    );
+    sp1_zkvm::io::commit_slice(&AgentPublicValues::abi_encode(&public_values));
 }
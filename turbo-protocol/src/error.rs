@@ -0,0 +1,56 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// The client speaks a protocol version the server does not support.
+    UnsupportedVersion,
+    /// A message other than `hello` was sent before the handshake.
+    HandshakeRequired,
+    /// The message could not be parsed.
+    InvalidMessage,
+    /// The message needs an authenticated connection.
+    AuthenticationRequired,
+    /// The signature of the challenge does not match the address.
+    InvalidSignature,
+    /// The player seed is malformed or not a BN254 G1 point.
+    InvalidSeed,
+    /// Spectators can't act on the session they watch.
+    ReadOnly,
+    /// The message needs a joined session.
+    NoActiveSession,
+    SessionNotFound,
+    /// The reducer or the action decoder rejected an action, or the action
+    /// is for a seat the player does not own.
+    InvalidAction,
+    ProofNotFound,
+    /// The server seed is only revealed once the game is over.
+    GameNotOver,
+    Internal,
+}
+
+/// Error reply. Errors never close the connection.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtocolError {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl ProtocolError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}: {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for ProtocolError {}
@@ -0,0 +1,8 @@
+pub mod error;
+pub mod message;
+
+pub use error::{ErrorCode, ProtocolError};
+pub use message::*;
+
+/// Version of the WebSocket protocol, checked during the `hello` handshake.
+pub const PROTOCOL_VERSION: u32 = 2;
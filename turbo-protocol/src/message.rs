@@ -0,0 +1,215 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::ProtocolError;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ProofType {
+    Core,
+    Compressed,
+    Groth16,
+    Plonk,
+}
+
+impl FromStr for ProofType {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "core" => Ok(ProofType::Core),
+            "compressed" => Ok(ProofType::Compressed),
+            "groth16" => Ok(ProofType::Groth16),
+            "plonk" => Ok(ProofType::Plonk),
+            _ => Err("Invalid proof type"),
+        }
+    }
+}
+
+/// Messages sent by clients. Every message is tagged by its `type`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// Must be the first message of a connection.
+    Hello {
+        version: u32,
+    },
+    /// Prove ownership of `address` with an EIP-191 signature of the
+    /// challenge sent in `welcome`. Required before joining a session.
+    Authenticate {
+        address: String,
+        signature: String,
+    },
+    /// Join `session_id`, or a new session built from `game_config`. Joining
+    /// a session again gives back the seat owned by the address.
+    ///
+    /// `seed` is a hex encoded BN254 G1 point (big-endian `x || y`) mixed into
+    /// the randomness of the seat. The server picks one when missing.
+    JoinSession {
+        #[serde(default)]
+        session_id: Option<String>,
+        #[serde(default)]
+        game_config: Option<Vec<u8>>,
+        #[serde(default)]
+        seed: Option<String>,
+    },
+    /// Watch `session_id` without taking a seat. The public state is pushed
+    /// whenever it changes. No authentication is needed.
+    Spectate {
+        session_id: String,
+    },
+    /// Actions as an array of JSON actions or a hex encoded action stream.
+    /// Only actions for the seat of the connection are accepted.
+    Action {
+        actions: Value,
+    },
+    /// Queue a proof of the session. `backend` names the prover backend to
+    /// use instead of the one the server routes `proof_type` to.
+    Proof {
+        proof_type: ProofType,
+        #[serde(default)]
+        backend: Option<String>,
+    },
+    /// Status of `proof_id`, or of the last proof requested on this connection.
+    ProofStatus {
+        #[serde(default)]
+        proof_id: Option<String>,
+    },
+    /// Take back the last action of the player, if the game allows it.
+    Undo,
+    /// Reveal the server seed once the game is over. Only players with a seat
+    /// in the session can reveal it.
+    Reveal,
+    /// Leave the session. It is closed for everyone once every player has left
+    /// or the game is over.
+    CloseSession,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ProofStatus {
+    Queued,
+    InProgress,
+    Done { proof: Value },
+    Error { error: String },
+}
+
+/// Messages sent by the server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Welcome {
+        version: u32,
+        /// Message to sign for `authenticate`.
+        challenge: String,
+    },
+    Authenticated {
+        address: String,
+    },
+    Joined {
+        session_id: String,
+        player_idx: usize,
+        server_commitment: String,
+        /// Commitment to the seed combined from the server and player seeds.
+        seed_commitment: String,
+    },
+    Spectating {
+        session_id: String,
+        public_state: Value,
+    },
+    /// Game state after a batch of actions, as seen by the player.
+    State {
+        public_state: Value,
+        client_response: Value,
+    },
+    ProofQueued {
+        proof_id: String,
+    },
+    ProofStatus {
+        proof_id: String,
+        #[serde(flatten)]
+        status: ProofStatus,
+    },
+    Revealed {
+        reveal: Value,
+    },
+    Closed {
+        session_id: String,
+    },
+    /// The connection left a session other players are still in.
+    Left {
+        session_id: String,
+    },
+    Error {
+        #[serde(flatten)]
+        error: ProtocolError,
+    },
+}
+
+/// A client message with the id used to correlate its reply.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientEnvelope {
+    #[serde(default)]
+    pub id: Option<u64>,
+    #[serde(flatten)]
+    pub message: ClientMessage,
+}
+
+/// A server message, carrying the id of the request it answers. Messages
+/// pushed by the server on its own (state changes caused by other players,
+/// proof status updates, session closes) have no id.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerEnvelope {
+    #[serde(default)]
+    pub id: Option<u64>,
+    #[serde(flatten)]
+    pub message: ServerMessage,
+}
+
+impl ServerEnvelope {
+    pub fn reply(id: Option<u64>, result: Result<ServerMessage, ProtocolError>) -> Self {
+        Self {
+            id,
+            message: result.unwrap_or_else(|error| ServerMessage::Error { error }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorCode;
+
+    #[test]
+    fn envelope_format() {
+        let envelope: ClientEnvelope =
+            serde_json::from_str(r#"{"id":7,"type":"proof","proof_type":"groth16"}"#).unwrap();
+        assert_eq!(envelope.id, Some(7));
+        assert!(matches!(
+            envelope.message,
+            ClientMessage::Proof {
+                proof_type: ProofType::Groth16,
+                backend: None,
+            }
+        ));
+
+        let reply = ServerEnvelope::reply(
+            Some(7),
+            Err(ProtocolError::new(
+                ErrorCode::NoActiveSession,
+                "No active session",
+            )),
+        );
+        assert_eq!(
+            serde_json::to_value(&reply).unwrap(),
+            serde_json::json!({
+                "id": 7,
+                "type": "error",
+                "code": "no_active_session",
+                "message": "No active session"
+            })
+        );
+    }
+}
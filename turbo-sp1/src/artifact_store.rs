@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex as StdMutex;
+
+use serde::{Deserialize, Serialize};
+use turbo_protocol::ProofType;
+
+/// Files kept for each finished proof.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ArtifactKind {
+    /// Bincode encoded `SP1ProofWithPublicValues`, loadable with
+    /// `SP1ProofWithPublicValues::load`.
+    Proof,
+    /// Match transcript, see `crate::transcript`.
+    Transcript,
+    /// JSON encoded `ProofMetadata`.
+    Metadata,
+}
+
+impl ArtifactKind {
+    fn extension(self) -> &'static str {
+        match self {
+            ArtifactKind::Proof => "bin",
+            ArtifactKind::Transcript => "transcript",
+            ArtifactKind::Metadata => "json",
+        }
+    }
+}
+
+/// Wall clock time in milliseconds spent on each stage of a proof.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProofTimings {
+    pub execute_ms: u64,
+    pub setup_ms: u64,
+    pub prove_ms: u64,
+}
+
+/// Summary of a stored proof.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProofMetadata {
+    pub proof_id: String,
+    pub proof_type: ProofType,
+    pub vkey: String,
+    /// Hex encoded ABI `TurboPublicValues`.
+    pub public_values: String,
+    pub cycle_count: u64,
+    pub timings: ProofTimings,
+}
+
+pub trait ArtifactStore: Send + Sync {
+    fn put(&self, id: &str, kind: ArtifactKind, data: &[u8]) -> Result<(), &'static str>;
+    fn get(&self, id: &str, kind: ArtifactKind) -> Result<Option<Vec<u8>>, &'static str>;
+    /// Remove every artifact of proof `id`.
+    fn remove(&self, id: &str) -> Result<(), &'static str>;
+
+    fn put_metadata(&self, metadata: &ProofMetadata) -> Result<(), &'static str> {
+        let data = serde_json::to_vec(metadata).map_err(|_| "Failed to serialize metadata")?;
+        self.put(&metadata.proof_id, ArtifactKind::Metadata, &data)
+    }
+
+    fn get_metadata(&self, id: &str) -> Result<Option<ProofMetadata>, &'static str> {
+        self.get(id, ArtifactKind::Metadata)?
+            .map(|data| serde_json::from_slice(&data).map_err(|_| "Failed to deserialize metadata"))
+            .transpose()
+    }
+}
+
+/// Keeps artifacts in memory, so they do not outlive the process.
+#[derive(Default)]
+pub struct MemoryArtifactStore {
+    artifacts: StdMutex<HashMap<(String, ArtifactKind), Vec<u8>>>,
+}
+
+impl MemoryArtifactStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ArtifactStore for MemoryArtifactStore {
+    fn put(&self, id: &str, kind: ArtifactKind, data: &[u8]) -> Result<(), &'static str> {
+        let mut artifacts = self.artifacts.lock().map_err(|_| "Failed to lock store")?;
+        artifacts.insert((id.to_string(), kind), data.to_vec());
+        Ok(())
+    }
+
+    fn get(&self, id: &str, kind: ArtifactKind) -> Result<Option<Vec<u8>>, &'static str> {
+        let artifacts = self.artifacts.lock().map_err(|_| "Failed to lock store")?;
+        Ok(artifacts.get(&(id.to_string(), kind)).cloned())
+    }
+
+    fn remove(&self, id: &str) -> Result<(), &'static str> {
+        let mut artifacts = self.artifacts.lock().map_err(|_| "Failed to lock store")?;
+        artifacts.retain(|(artifact_id, _), _| artifact_id != id);
+        Ok(())
+    }
+}
+
+/// Stores the artifacts of proof `id` as `<dir>/<id>.bin`,
+/// `<dir>/<id>.transcript` and `<dir>/<id>.json`.
+pub struct FileArtifactStore {
+    dir: PathBuf,
+}
+
+impl FileArtifactStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self, &'static str> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir).map_err(|_| "Failed to create artifact directory")?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, id: &str, kind: ArtifactKind) -> Result<PathBuf, &'static str> {
+        // Proof ids are UUIDs, anything else could escape the directory
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err("Invalid proof id");
+        }
+        Ok(self.dir.join(format!("{}.{}", id, kind.extension())))
+    }
+}
+
+impl ArtifactStore for FileArtifactStore {
+    fn put(&self, id: &str, kind: ArtifactKind, data: &[u8]) -> Result<(), &'static str> {
+        let path = self.path(id, kind)?;
+        let tmp_path = path.with_extension(format!("{}.tmp", kind.extension()));
+
+        fs::write(&tmp_path, data).map_err(|_| "Failed to write artifact")?;
+        fs::rename(&tmp_path, &path).map_err(|_| "Failed to write artifact")
+    }
+
+    fn get(&self, id: &str, kind: ArtifactKind) -> Result<Option<Vec<u8>>, &'static str> {
+        let path = self.path(id, kind)?;
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        fs::read(&path)
+            .map(Some)
+            .map_err(|_| "Failed to read artifact")
+    }
+
+    fn remove(&self, id: &str) -> Result<(), &'static str> {
+        for kind in [
+            ArtifactKind::Proof,
+            ArtifactKind::Transcript,
+            ArtifactKind::Metadata,
+        ] {
+            let path = self.path(id, kind)?;
+            if path.exists() {
+                fs::remove_file(&path).map_err(|_| "Failed to remove artifact")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_store_roundtrip() {
+        let dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let store = FileArtifactStore::new(&dir).unwrap();
+
+        let id = uuid::Uuid::new_v4().to_string();
+        store.put(&id, ArtifactKind::Proof, &[1, 2, 3]).unwrap();
+        store
+            .put_metadata(&ProofMetadata {
+                proof_id: id.clone(),
+                proof_type: ProofType::Core,
+                vkey: "0x00".into(),
+                public_values: "0x".into(),
+                cycle_count: 42,
+                timings: ProofTimings::default(),
+            })
+            .unwrap();
+
+        assert_eq!(
+            store.get(&id, ArtifactKind::Proof).unwrap(),
+            Some(vec![1, 2, 3])
+        );
+        assert_eq!(store.get_metadata(&id).unwrap().unwrap().cycle_count, 42);
+        assert!(store.get(&id, ArtifactKind::Transcript).unwrap().is_none());
+        assert!(store.get("../escape", ArtifactKind::Proof).is_err());
+
+        store.remove(&id).unwrap();
+        assert!(store.get(&id, ArtifactKind::Proof).unwrap().is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use std::sync::Mutex as StdMutex;
+use std::time::{Duration, Instant};
+
+use alloy::primitives::{Address, Signature};
+use serde::Deserialize;
+use uuid::Uuid;
+
+/// How long a challenge handed out over HTTP can be signed.
+const CHALLENGE_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Challenges kept at once. Issuing needs no authentication, so past this the
+/// oldest challenge is dropped.
+const MAX_OUTSTANDING_CHALLENGES: usize = 10_000;
+
+/// Fresh challenge for a connection to sign with `personal_sign` (EIP-191).
+pub fn new_challenge() -> String {
+    format!("Sign in to turbo-sp1\nNonce: {}", Uuid::new_v4())
+}
+
+/// Check that `signature` over `challenge` was made by the key of `address`,
+/// returning the address bytes.
+pub fn verify_challenge(
+    challenge: &str,
+    address: &str,
+    signature: &str,
+) -> Result<[u8; 20], &'static str> {
+    let address: Address = address.parse().map_err(|_| "Invalid address")?;
+    let signature_bytes =
+        hex::decode(signature.trim_start_matches("0x")).map_err(|_| "Invalid signature")?;
+    let signature =
+        Signature::try_from(signature_bytes.as_slice()).map_err(|_| "Invalid signature")?;
+
+    let signer = signature
+        .recover_address_from_msg(challenge)
+        .map_err(|_| "Invalid signature")?;
+    if signer != address {
+        return Err("Signature does not match address");
+    }
+
+    Ok(address.into())
+}
+
+/// Proof of ownership of `address` sent with HTTP requests: a signature of a
+/// challenge from `ChallengeStore::issue`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SignedChallenge {
+    pub challenge: String,
+    pub address: String,
+    pub signature: String,
+}
+
+/// Challenges handed out over HTTP, where there is no connection to keep one
+/// in. Each challenge can be used once, within `CHALLENGE_TTL`, and only the
+/// latest `MAX_OUTSTANDING_CHALLENGES` are kept.
+#[derive(Default)]
+pub struct ChallengeStore {
+    issued: StdMutex<HashMap<String, Instant>>,
+}
+
+impl ChallengeStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn issue(&self) -> Result<String, &'static str> {
+        let mut issued = self
+            .issued
+            .lock()
+            .map_err(|_| "Failed to lock challenges")?;
+        issued.retain(|_, issued_at| issued_at.elapsed() < CHALLENGE_TTL);
+        if issued.len() >= MAX_OUTSTANDING_CHALLENGES {
+            let oldest = issued
+                .iter()
+                .min_by_key(|(_, issued_at)| **issued_at)
+                .map(|(challenge, _)| challenge.clone());
+            if let Some(oldest) = oldest {
+                issued.remove(&oldest);
+            }
+        }
+
+        let challenge = new_challenge();
+        issued.insert(challenge.clone(), Instant::now());
+        Ok(challenge)
+    }
+
+    /// Consume the challenge of `signed` and check its signature, returning
+    /// the address bytes.
+    pub fn verify(&self, signed: &SignedChallenge) -> Result<[u8; 20], &'static str> {
+        let issued_at = self
+            .issued
+            .lock()
+            .map_err(|_| "Failed to lock challenges")?
+            .remove(&signed.challenge)
+            .ok_or("Unknown challenge")?;
+        if issued_at.elapsed() >= CHALLENGE_TTL {
+            return Err("Challenge expired");
+        }
+
+        verify_challenge(&signed.challenge, &signed.address, &signed.signature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::signers::{local::PrivateKeySigner, SignerSync};
+
+    #[test]
+    fn verify_signed_challenge() {
+        let signer = PrivateKeySigner::random();
+        let challenge = new_challenge();
+        let signature = signer.sign_message_sync(challenge.as_bytes()).unwrap();
+        let signature = hex::encode(signature.as_bytes());
+        let address = signer.address().to_string();
+
+        assert_eq!(
+            verify_challenge(&challenge, &address, &signature),
+            Ok(signer.address().into())
+        );
+        assert!(verify_challenge(&new_challenge(), &address, &signature).is_err());
+    }
+
+    #[test]
+    fn challenges_are_single_use() {
+        let signer = PrivateKeySigner::random();
+        let store = ChallengeStore::new();
+        let challenge = store.issue().unwrap();
+        let signed = SignedChallenge {
+            signature: hex::encode(
+                signer
+                    .sign_message_sync(challenge.as_bytes())
+                    .unwrap()
+                    .as_bytes(),
+            ),
+            challenge,
+            address: signer.address().to_string(),
+        };
+
+        assert_eq!(store.verify(&signed), Ok(signer.address().into()));
+        assert_eq!(store.verify(&signed), Err("Unknown challenge"));
+    }
+
+    #[test]
+    fn outstanding_challenges_are_capped() {
+        let store = ChallengeStore::new();
+        let first = store.issue().unwrap();
+        for _ in 0..MAX_OUTSTANDING_CHALLENGES {
+            store.issue().unwrap();
+        }
+
+        assert_eq!(
+            store.issued.lock().unwrap().len(),
+            MAX_OUTSTANDING_CHALLENGES
+        );
+        assert!(!store.issued.lock().unwrap().contains_key(&first));
+    }
+}
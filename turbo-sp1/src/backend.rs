@@ -0,0 +1,358 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex as StdMutex};
+
+use sp1_sdk::{
+    CpuProver, EnvProver, ExecutionReport, NetworkProver, ProverClient, SP1ProofWithPublicValues,
+    SP1ProvingKey, SP1PublicValues, SP1Stdin, SP1VerifyingKey,
+};
+
+use crate::proof::ProofType;
+
+/// A prover the server can execute, set up and prove circuits with.
+///
+/// Backends are object safe so the server can hold them as
+/// `Arc<dyn ProverBackend>` and pick one per deployment or per proof type.
+pub trait ProverBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn execute(
+        &self,
+        elf: &[u8],
+        stdin: &SP1Stdin,
+    ) -> Result<(SP1PublicValues, ExecutionReport), &'static str>;
+
+    fn setup(&self, elf: &[u8]) -> (SP1ProvingKey, SP1VerifyingKey);
+
+    fn prove(
+        &self,
+        pk: &SP1ProvingKey,
+        stdin: &SP1Stdin,
+        proof_type: ProofType,
+    ) -> Result<SP1ProofWithPublicValues, &'static str>;
+
+    fn verify(
+        &self,
+        proof: &SP1ProofWithPublicValues,
+        vk: &SP1VerifyingKey,
+    ) -> Result<(), &'static str>;
+}
+
+/// Run a prove builder in the mode requested by `proof_type`.
+macro_rules! prove_with_type {
+    ($prover:expr, $pk:expr, $stdin:expr, $proof_type:expr) => {
+        match $proof_type {
+            ProofType::Core => $prover.prove($pk, $stdin).core().run(),
+            ProofType::Compressed => $prover.prove($pk, $stdin).compressed().run(),
+            ProofType::Groth16 => $prover.prove($pk, $stdin).groth16().run(),
+            ProofType::Plonk => $prover.prove($pk, $stdin).plonk().run(),
+        }
+        .map_err(|_| "Failed to generate proof")
+    };
+}
+
+/// Prover configured through the `SP1_PROVER` environment variable.
+pub struct EnvBackend {
+    prover: EnvProver,
+}
+
+impl EnvBackend {
+    pub fn new() -> Self {
+        Self {
+            prover: ProverClient::from_env(),
+        }
+    }
+}
+
+impl Default for EnvBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProverBackend for EnvBackend {
+    fn name(&self) -> &'static str {
+        "env"
+    }
+
+    fn execute(
+        &self,
+        elf: &[u8],
+        stdin: &SP1Stdin,
+    ) -> Result<(SP1PublicValues, ExecutionReport), &'static str> {
+        self.prover
+            .execute(elf, stdin)
+            .run()
+            .map_err(|_| "Failed to execute circuit")
+    }
+
+    fn setup(&self, elf: &[u8]) -> (SP1ProvingKey, SP1VerifyingKey) {
+        self.prover.setup(elf)
+    }
+
+    fn prove(
+        &self,
+        pk: &SP1ProvingKey,
+        stdin: &SP1Stdin,
+        proof_type: ProofType,
+    ) -> Result<SP1ProofWithPublicValues, &'static str> {
+        prove_with_type!(self.prover, pk, stdin, proof_type)
+    }
+
+    fn verify(
+        &self,
+        proof: &SP1ProofWithPublicValues,
+        vk: &SP1VerifyingKey,
+    ) -> Result<(), &'static str> {
+        self.prover
+            .verify(proof, vk)
+            .map_err(|_| "Failed to verify proof")
+    }
+}
+
+/// Local CPU prover. The mock variant executes the program for real but
+/// skips proof generation, which makes it usable in CI without a GPU.
+pub struct CpuBackend {
+    prover: CpuProver,
+    mock: bool,
+}
+
+impl CpuBackend {
+    pub fn new() -> Self {
+        Self {
+            prover: ProverClient::builder().cpu().build(),
+            mock: false,
+        }
+    }
+
+    pub fn mock() -> Self {
+        Self {
+            prover: ProverClient::builder().mock().build(),
+            mock: true,
+        }
+    }
+}
+
+impl Default for CpuBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProverBackend for CpuBackend {
+    fn name(&self) -> &'static str {
+        if self.mock {
+            "mock"
+        } else {
+            "cpu"
+        }
+    }
+
+    fn execute(
+        &self,
+        elf: &[u8],
+        stdin: &SP1Stdin,
+    ) -> Result<(SP1PublicValues, ExecutionReport), &'static str> {
+        self.prover
+            .execute(elf, stdin)
+            .run()
+            .map_err(|_| "Failed to execute circuit")
+    }
+
+    fn setup(&self, elf: &[u8]) -> (SP1ProvingKey, SP1VerifyingKey) {
+        self.prover.setup(elf)
+    }
+
+    fn prove(
+        &self,
+        pk: &SP1ProvingKey,
+        stdin: &SP1Stdin,
+        proof_type: ProofType,
+    ) -> Result<SP1ProofWithPublicValues, &'static str> {
+        prove_with_type!(self.prover, pk, stdin, proof_type)
+    }
+
+    fn verify(
+        &self,
+        proof: &SP1ProofWithPublicValues,
+        vk: &SP1VerifyingKey,
+    ) -> Result<(), &'static str> {
+        self.prover
+            .verify(proof, vk)
+            .map_err(|_| "Failed to verify proof")
+    }
+}
+
+/// Succinct Prover Network backend.
+///
+/// Only proving goes over the network. Execution, key setup and verification
+/// run on a local CPU prover standing in for the network, so a server can
+/// reject bad action logs before paying for a remote proof.
+pub struct NetworkBackend {
+    prover: NetworkProver,
+    local: CpuProver,
+}
+
+impl NetworkBackend {
+    /// Build the backend from `NETWORK_PRIVATE_KEY`.
+    pub fn from_env() -> Result<Self, &'static str> {
+        let private_key = std::env::var("NETWORK_PRIVATE_KEY")
+            .ok()
+            .filter(|key| !key.is_empty())
+            .ok_or("NETWORK_PRIVATE_KEY is not set")?;
+
+        Ok(Self {
+            prover: ProverClient::builder()
+                .network()
+                .private_key(&private_key)
+                .build(),
+            local: ProverClient::builder().cpu().build(),
+        })
+    }
+}
+
+impl ProverBackend for NetworkBackend {
+    fn name(&self) -> &'static str {
+        "network"
+    }
+
+    fn execute(
+        &self,
+        elf: &[u8],
+        stdin: &SP1Stdin,
+    ) -> Result<(SP1PublicValues, ExecutionReport), &'static str> {
+        self.local
+            .execute(elf, stdin)
+            .run()
+            .map_err(|_| "Failed to execute circuit")
+    }
+
+    fn setup(&self, elf: &[u8]) -> (SP1ProvingKey, SP1VerifyingKey) {
+        self.local.setup(elf)
+    }
+
+    fn prove(
+        &self,
+        pk: &SP1ProvingKey,
+        stdin: &SP1Stdin,
+        proof_type: ProofType,
+    ) -> Result<SP1ProofWithPublicValues, &'static str> {
+        prove_with_type!(self.prover, pk, stdin, proof_type)
+    }
+
+    fn verify(
+        &self,
+        proof: &SP1ProofWithPublicValues,
+        vk: &SP1VerifyingKey,
+    ) -> Result<(), &'static str> {
+        self.local
+            .verify(proof, vk)
+            .map_err(|_| "Failed to verify proof")
+    }
+}
+
+/// Names accepted by `backend_from_name`.
+pub const BACKEND_NAMES: [&str; 4] = ["env", "cpu", "mock", "network"];
+
+/// Build a backend from its name: `env`, `cpu`, `mock` or `network`.
+pub fn backend_from_name(name: &str) -> Result<Arc<dyn ProverBackend>, &'static str> {
+    match name {
+        "env" => Ok(Arc::new(EnvBackend::new())),
+        "cpu" => Ok(Arc::new(CpuBackend::new())),
+        "mock" => Ok(Arc::new(CpuBackend::mock())),
+        "network" => Ok(Arc::new(NetworkBackend::from_env()?)),
+        _ => Err("Unknown prover backend"),
+    }
+}
+
+/// Picks the backend used for each proof type, falling back to a default
+/// backend which is also used to execute circuits. Requests can also name
+/// the backend to prove with.
+#[derive(Clone)]
+pub struct ProverRouter {
+    default: Arc<dyn ProverBackend>,
+    routes: HashMap<ProofType, Arc<dyn ProverBackend>>,
+    /// Backends named by requests, built on first use.
+    named: Arc<StdMutex<HashMap<String, Arc<dyn ProverBackend>>>>,
+}
+
+impl ProverRouter {
+    pub fn new(default: Arc<dyn ProverBackend>) -> Self {
+        Self {
+            default,
+            routes: HashMap::new(),
+            named: Arc::new(StdMutex::new(HashMap::new())),
+        }
+    }
+
+    /// Use `backend` for every `proof_type` proof.
+    pub fn route(mut self, proof_type: ProofType, backend: Arc<dyn ProverBackend>) -> Self {
+        self.routes.insert(proof_type, backend);
+        self
+    }
+
+    /// Build the router from the environment.
+    ///
+    /// `TURBO_PROVER` names the default backend (`env` when unset), and
+    /// `TURBO_PROVER_CORE`, `TURBO_PROVER_COMPRESSED`, `TURBO_PROVER_GROTH16`
+    /// and `TURBO_PROVER_PLONK` override it per proof type.
+    pub fn from_env() -> Result<Self, &'static str> {
+        let default_name = std::env::var("TURBO_PROVER").unwrap_or_else(|_| "env".into());
+        let mut router = Self::new(backend_from_name(&default_name)?);
+
+        for (proof_type, var) in [
+            (ProofType::Core, "TURBO_PROVER_CORE"),
+            (ProofType::Compressed, "TURBO_PROVER_COMPRESSED"),
+            (ProofType::Groth16, "TURBO_PROVER_GROTH16"),
+            (ProofType::Plonk, "TURBO_PROVER_PLONK"),
+        ] {
+            if let Ok(name) = std::env::var(var) {
+                let backend = if name == default_name {
+                    router.default.clone()
+                } else {
+                    backend_from_name(&name)?
+                };
+                router = router.route(proof_type, backend);
+            }
+        }
+
+        Ok(router)
+    }
+
+    /// Backend used to execute circuits.
+    pub fn default_backend(&self) -> Arc<dyn ProverBackend> {
+        self.default.clone()
+    }
+
+    /// Backend used to generate `proof_type` proofs.
+    pub fn backend(&self, proof_type: &ProofType) -> Arc<dyn ProverBackend> {
+        self.routes
+            .get(proof_type)
+            .cloned()
+            .unwrap_or_else(|| self.default.clone())
+    }
+
+    /// Backend named `name`, or the one routed for `proof_type` when `None`.
+    pub fn backend_for(
+        &self,
+        proof_type: &ProofType,
+        name: Option<&str>,
+    ) -> Result<Arc<dyn ProverBackend>, &'static str> {
+        let Some(name) = name else {
+            return Ok(self.backend(proof_type));
+        };
+        if self.default.name() == name {
+            return Ok(self.default.clone());
+        }
+
+        let mut named = self
+            .named
+            .lock()
+            .map_err(|_| "Failed to lock prover backends")?;
+        if let Some(backend) = named.get(name) {
+            return Ok(backend.clone());
+        }
+        let backend = backend_from_name(name)?;
+        named.insert(name.to_string(), backend.clone());
+        Ok(backend)
+    }
+}
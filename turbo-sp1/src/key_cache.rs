@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex as StdMutex, OnceLock};
+
+use alloy::primitives::keccak256;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use sp1_sdk::{SP1ProvingKey, SP1VerifyingKey, SP1_CIRCUIT_VERSION};
+
+/// Proving and verifying keys of a program.
+pub type ProgramKeys = Arc<(SP1ProvingKey, SP1VerifyingKey)>;
+
+lazy_static! {
+    static ref KEY_CACHE: KeyCache = KeyCache::from_env();
+}
+
+/// Digest identifying a program in the cache.
+pub fn elf_digest(elf: &[u8]) -> [u8; 32] {
+    keccak256(elf).into()
+}
+
+#[derive(Serialize)]
+struct KeyFileRef<'a> {
+    sdk_version: &'a str,
+    elf_digest: [u8; 32],
+    pk: &'a SP1ProvingKey,
+    vk: &'a SP1VerifyingKey,
+}
+
+#[derive(Deserialize)]
+struct KeyFile {
+    sdk_version: String,
+    elf_digest: [u8; 32],
+    pk: SP1ProvingKey,
+    vk: SP1VerifyingKey,
+}
+
+/// Cache of program keys keyed by ELF digest, shared by the server, the
+/// zeromind pipeline and the scripts.
+///
+/// Keys are set up once per process. When the cache has a directory they are
+/// also stored as `<dir>/<digest>.keys`, and entries written by another SP1
+/// version are set up again. Stored keys are trusted as they are, so tools
+/// vouching for a program, like `verify` and `vkey`, set its keys up directly.
+pub struct KeyCache {
+    dir: Option<PathBuf>,
+    slots: StdMutex<HashMap<[u8; 32], Arc<OnceLock<ProgramKeys>>>>,
+}
+
+impl KeyCache {
+    pub fn new(dir: Option<PathBuf>) -> Self {
+        Self {
+            dir,
+            slots: StdMutex::new(HashMap::new()),
+        }
+    }
+
+    /// Cache in `TURBO_KEY_CACHE_DIR`, `keys` by default. An empty value
+    /// keeps keys in memory only.
+    pub fn from_env() -> Self {
+        let dir = std::env::var("TURBO_KEY_CACHE_DIR").unwrap_or_else(|_| "keys".to_string());
+        Self::new((!dir.is_empty()).then(|| dir.into()))
+    }
+
+    /// Cache used across the process.
+    pub fn global() -> &'static KeyCache {
+        &KEY_CACHE
+    }
+
+    /// Keys of `elf`, running `setup` only if they are neither in memory nor
+    /// on disk. The cache is only locked to find the slot of `elf`, so
+    /// different programs are set up concurrently.
+    pub fn get_or_setup(
+        &self,
+        elf: &[u8],
+        setup: impl FnOnce() -> (SP1ProvingKey, SP1VerifyingKey),
+    ) -> Result<ProgramKeys, &'static str> {
+        let digest = elf_digest(elf);
+        let slot = self
+            .slots
+            .lock()
+            .map_err(|_| "Failed to lock key cache")?
+            .entry(digest)
+            .or_default()
+            .clone();
+
+        Ok(slot
+            .get_or_init(|| {
+                if let Some(keys) = self.load(&digest) {
+                    return keys;
+                }
+
+                let (pk, vk) = setup();
+                if let Err(e) = self.store(&digest, &pk, &vk) {
+                    eprintln!("Failed to store keys {}: {}", hex::encode(digest), e);
+                }
+                Arc::new((pk, vk))
+            })
+            .clone())
+    }
+
+    fn path(&self, digest: &[u8; 32]) -> Option<PathBuf> {
+        Some(
+            self.dir
+                .as_ref()?
+                .join(format!("{}.keys", hex::encode(digest))),
+        )
+    }
+
+    fn load(&self, digest: &[u8; 32]) -> Option<ProgramKeys> {
+        let data = fs::read(self.path(digest)?).ok()?;
+        let file: KeyFile = bincode::deserialize(&data).ok()?;
+
+        // Keys of another SP1 version do not match its circuits
+        if file.sdk_version != SP1_CIRCUIT_VERSION || &file.elf_digest != digest {
+            return None;
+        }
+        Some(Arc::new((file.pk, file.vk)))
+    }
+
+    fn store(
+        &self,
+        digest: &[u8; 32],
+        pk: &SP1ProvingKey,
+        vk: &SP1VerifyingKey,
+    ) -> Result<(), &'static str> {
+        let Some(path) = self.path(digest) else {
+            return Ok(());
+        };
+        if let Some(dir) = &self.dir {
+            fs::create_dir_all(dir).map_err(|_| "Failed to create key cache directory")?;
+        }
+
+        let data = bincode::serialize(&KeyFileRef {
+            sdk_version: SP1_CIRCUIT_VERSION,
+            elf_digest: *digest,
+            pk,
+            vk,
+        })
+        .map_err(|_| "Failed to serialize keys")?;
+
+        let tmp_path = path.with_extension("keys.tmp");
+        fs::write(&tmp_path, data).map_err(|_| "Failed to write keys")?;
+        fs::rename(&tmp_path, &path).map_err(|_| "Failed to write keys")
+    }
+}
@@ -1,12 +1,20 @@
 pub use turbo_program::*;
+pub mod artifact_store;
+pub mod auth;
+pub mod backend;
+pub mod key_cache;
 pub mod proof;
 pub mod proof_worker;
 pub mod prove_queue;
+pub mod replay;
 pub mod server;
 pub mod session;
 pub mod session_manager;
 pub mod session_simple;
+pub mod session_store;
+pub mod transcript;
 pub mod warp;
+pub mod ws;
 pub mod zeromind;
 
 pub fn add(left: u64, right: u64) -> u64 {
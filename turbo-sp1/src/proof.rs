@@ -1,46 +1,66 @@
-use lazy_static::lazy_static;
 use sp1_sdk::SP1ProofWithPublicValues;
-use std::collections::HashMap;
 use std::sync::Arc;
-use std::sync::Mutex as StdMutex;
+use std::time::Instant;
 
 use alloy_sol_types::SolValue;
-use serde::{Deserialize, Serialize};
+use serde::Serialize;
 use serde_json::json;
-use sp1_sdk::{
-    EnvProver, ExecutionReport, HashableKey, SP1ProvingKey, SP1PublicValues, SP1VerifyingKey,
-};
+use sp1_sdk::{ExecutionReport, HashableKey, SP1PublicValues, SP1Stdin};
 use tokio::sync::Mutex;
-use turbo_program::traits::TurboActionSerialization;
+use turbo_program::{public_values::TurboPublicValues, traits::TurboActionSerialization};
 
+use crate::artifact_store::{ArtifactKind, ArtifactStore, ProofMetadata, ProofTimings};
+use crate::backend::ProverBackend;
+use crate::key_cache::{KeyCache, ProgramKeys};
 use crate::session::TurboSession;
+use crate::transcript::{Transcript, TranscriptProof};
 
-lazy_static! {
-    static ref SETUP_CACHE: StdMutex<HashMap<Vec<u8>, Arc<(SP1ProvingKey, SP1VerifyingKey)>>> =
-        StdMutex::new(HashMap::new());
+pub use turbo_protocol::ProofType;
+
+/// Decode the `TurboPublicValues` envelope committed by `turbo_program` along
+/// with the public state it wraps.
+pub fn decode_public_values<
+    PublicState: SolValue
+        + From<<<PublicState as SolValue>::SolType as alloy_sol_types::SolType>::RustType>,
+>(
+    public_values: &[u8],
+) -> Result<(TurboPublicValues, PublicState), &'static str> {
+    let envelope = TurboPublicValues::abi_decode(public_values)
+        .map_err(|_| "Failed to decode public values")?;
+    let state = PublicState::abi_decode(&envelope.public_state)
+        .map_err(|_| "Failed to decode output state")?;
+    Ok((envelope, state))
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub enum ProofType {
-    Core,
-    Compressed,
-    Groth16,
-    Plonk,
+pub fn commitments_json(envelope: &TurboPublicValues) -> serde_json::Value {
+    json!({
+        "match_nonce": envelope.match_nonce.to_string(),
+        "match_digest": envelope.match_digest.to_string(),
+        "server_seed_hash": envelope.server_seed_hash.to_string(),
+        "player_seed_hashes": envelope
+            .player_seed_hashes
+            .iter()
+            .map(|hash| hash.to_string())
+            .collect::<Vec<_>>(),
+        "player_addresses": envelope
+            .player_addresses
+            .iter()
+            .map(|address| address.to_string())
+            .collect::<Vec<_>>(),
+        "action_hashes": envelope
+            .action_hashes
+            .iter()
+            .map(|hash| hash.to_string())
+            .collect::<Vec<_>>(),
+        "private_state_hash": envelope.private_state_hash.to_string(),
+    })
 }
 
 async fn setup_circuit(
-    client: Arc<EnvProver>,
+    client: Arc<dyn ProverBackend>,
     elf: Arc<Vec<u8>>,
-) -> Result<Arc<(SP1ProvingKey, SP1VerifyingKey)>, &'static str> {
-    let mut cache = SETUP_CACHE.lock().map_err(|_| "Failed to lock cache")?;
-    if let Some(arc) = cache.get(&elf[..]) {
-        return Ok(arc.clone());
-    } else {
-        let (pk, vk) = client.setup(&elf);
-        let arc = Arc::new((pk.clone(), vk.clone()));
-        cache.insert(elf.to_vec(), arc.clone());
-        Ok(arc)
-    }
+) -> Result<ProgramKeys, &'static str> {
+    KeyCache::global().get_or_setup(&elf, || client.setup(&elf))
 }
 
 async fn execute_circuit<
@@ -54,17 +74,14 @@ async fn execute_circuit<
     GameAction: TurboActionSerialization + Send + Sync,
 >(
     session: Arc<Mutex<TurboSession<PublicState, PrivateState, GameAction>>>,
-    client: Arc<EnvProver>,
+    client: Arc<dyn ProverBackend>,
     elf: Arc<Vec<u8>>,
 ) -> Result<(SP1PublicValues, ExecutionReport), &'static str> {
     // Setup the inputs
     let stdin = session.lock().await.sp1_stdin();
 
     // Try executing the circuit first
-    client
-        .execute(&elf, &stdin)
-        .run()
-        .map_err(|_| "Failed to execute circuit")
+    client.execute(&elf, &stdin)
 }
 
 pub async fn handle_proof_execute<
@@ -78,90 +95,96 @@ pub async fn handle_proof_execute<
     GameAction: TurboActionSerialization + Send + Sync,
 >(
     session: Arc<Mutex<TurboSession<PublicState, PrivateState, GameAction>>>,
-    client: Arc<EnvProver>,
+    client: Arc<dyn ProverBackend>,
     elf: Arc<Vec<u8>>,
 ) -> Result<serde_json::Value, &'static str> {
     let (output, report) = execute_circuit(session, client, elf).await?;
 
-    let state: PublicState =
-        PublicState::abi_decode(output.as_slice()).map_err(|_| "Failed to decode output state")?;
+    let (envelope, state) = decode_public_values::<PublicState>(output.as_slice())?;
     Ok(json!({
         "cycle_count": report.total_instruction_count(),
-        "state": state
+        "state": state,
+        "commitments": commitments_json(&envelope)
     }))
 }
 
 pub async fn handle_proof_request<
-    PublicState: Default
-        + SolValue
+    PublicState: SolValue
         + Serialize
         + From<<<PublicState as SolValue>::SolType as alloy_sol_types::SolType>::RustType>
         + Send
         + Sync,
-    PrivateState: Default + Send + Sync,
-    GameAction: TurboActionSerialization + Send + Sync,
 >(
-    session: Arc<Mutex<TurboSession<PublicState, PrivateState, GameAction>>>,
-    client: Arc<EnvProver>,
+    stdin: SP1Stdin,
+    client: Arc<dyn ProverBackend>,
     elf: Arc<Vec<u8>>,
+    artifacts: Arc<dyn ArtifactStore>,
     proof_type: ProofType,
     proof_id: String,
 ) -> Result<serde_json::Value, &'static str> {
-    // Setup the inputs
-    let stdin = session.lock().await.sp1_stdin();
-
     // Try executing the circuit first
-    let (_, report) = client
-        .execute(&elf, &stdin)
-        .run()
-        .map_err(|_| "Failed to execute circuit")?;
+    let execute_start = Instant::now();
+    let (_, report) = client.execute(&elf, &stdin)?;
+    let execute_duration = execute_start.elapsed();
 
+    let setup_start = Instant::now();
     let setup_arc = setup_circuit(client.clone(), elf).await?;
     let pk = &setup_arc.0;
     let vk = &setup_arc.1;
-
-    let proof = match proof_type {
-        ProofType::Core => client
-            .prove(pk, &stdin)
-            .run()
-            .expect("failed to generate proof"),
-        ProofType::Compressed => client
-            .prove(pk, &stdin)
-            .compressed()
-            .run()
-            .expect("failed to generate proof"),
-        ProofType::Groth16 => client
-            .prove(pk, &stdin)
-            .groth16()
-            .run()
-            .expect("failed to generate proof"),
-        ProofType::Plonk => client
-            .prove(pk, &stdin)
-            .plonk()
-            .run()
-            .expect("failed to generate proof"),
+    let setup_duration = setup_start.elapsed();
+
+    let prove_start = Instant::now();
+    let proof = client.prove(pk, &stdin, proof_type)?;
+    let prove_duration = prove_start.elapsed();
+
+    let (envelope, state) = decode_public_values::<PublicState>(proof.public_values.as_slice())?;
+
+    let metadata = ProofMetadata {
+        proof_id: proof_id.clone(),
+        proof_type,
+        vkey: vk.bytes32().to_string(),
+        public_values: format!("0x{}", hex::encode(proof.public_values.as_slice())),
+        cycle_count: report.total_instruction_count(),
+        timings: ProofTimings {
+            execute_ms: execute_duration.as_millis() as u64,
+            setup_ms: setup_duration.as_millis() as u64,
+            prove_ms: prove_duration.as_millis() as u64,
+        },
     };
 
-    let state: PublicState = PublicState::abi_decode(proof.public_values.as_slice()).unwrap();
-
-    std::fs::create_dir_all("proofs").map_err(|_| "Failed to create proofs directory")?;
-    proof
-        .save(format!("proofs/{}.bin", proof_id))
-        .map_err(|_| "Failed to save proof")?;
+    let proof_bytes = bincode::serialize(&proof).map_err(|_| "Failed to serialize proof")?;
+    artifacts.put(&proof_id, ArtifactKind::Proof, &proof_bytes)?;
+
+    let mut transcript = Transcript::from_stdin(&stdin)?;
+    transcript.public_state = envelope.public_state.to_vec();
+    transcript.proof = Some(TranscriptProof {
+        vk: vk.clone(),
+        proof: proof.clone(),
+    });
+    artifacts.put(
+        &proof_id,
+        ArtifactKind::Transcript,
+        &transcript.to_binary()?,
+    )?;
+    artifacts.put_metadata(&metadata)?;
 
     Ok(match proof_type {
         ProofType::Core | ProofType::Compressed => json!({
-            "vkey": vk.bytes32().to_string(),
-            "public_values": format!("0x{}", hex::encode(proof.public_values.as_slice())),
+            "vkey": metadata.vkey,
+            "public_values": metadata.public_values,
             "state": state,
-            "cycle_count": report.total_instruction_count()
+            "commitments": commitments_json(&envelope),
+            "cycle_count": metadata.cycle_count,
+            "timings": metadata.timings
         }),
         ProofType::Groth16 | ProofType::Plonk => json!({
-            "vkey": vk.bytes32().to_string(),
-            "public_values": format!("0x{}", hex::encode(proof.public_values.as_slice())),
+            "vkey": metadata.vkey,
+            "public_values": metadata.public_values,
             "proof": format!("0x{}", hex::encode(proof.bytes())),
             "state": state,
-            "cycle_count": report.total_instruction_count()
+            "commitments": commitments_json(&envelope),
+            "cycle_count": metadata.cycle_count,
+            "timings": metadata.timings
         }),
     })
 }
@@ -1,74 +1,138 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use alloy_sol_types::SolValue;
-use serde::Serialize;
-use sp1_sdk::EnvProver;
+use serde::{Deserialize, Serialize};
+use sp1_sdk::SP1Stdin;
 use tokio::sync::{mpsc, Mutex};
-use turbo_program::traits::TurboActionSerialization;
 
 use crate::{
+    artifact_store::ArtifactStore,
+    backend::ProverRouter,
     proof::{handle_proof_request, ProofType},
     prove_queue::{ProveQueue, ProveStatus},
-    session::TurboSession,
 };
 
 type TaskId = String;
-pub type ProofJob<PublicState, PrivateState, GameAction> =
-    (TaskId, ProofRequest<PublicState, PrivateState, GameAction>);
-
-#[derive(Clone)]
-pub struct ProofRequest<
-    PublicState: Serialize + Default + Send + Sync,
-    PrivateState: Default + Send + Sync,
-    GameAction: TurboActionSerialization + Send + Sync,
-> {
-    session: Arc<Mutex<TurboSession<PublicState, PrivateState, GameAction>>>,
-    proof_type: ProofType,
-    client: Arc<EnvProver>,
-    elf: Arc<Vec<u8>>,
+pub type ProofJob = (TaskId, ProofRequest);
+
+/// A proof job. The program input is snapshotted when the job is queued, so
+/// it can be persisted and replayed without the session that produced it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProofRequest {
+    pub session_id: String,
+    pub proof_type: ProofType,
+    pub stdin: SP1Stdin,
+    /// Backend to prove with instead of the one the router picks.
+    #[serde(default)]
+    pub backend: Option<String>,
 }
 
-impl<PublicState, PrivateState, GameAction> ProofRequest<PublicState, PrivateState, GameAction>
-where
-    PublicState: Serialize + Default + Send + Sync,
-    PrivateState: Default + Send + Sync,
-    GameAction: TurboActionSerialization + Send + Sync,
-{
+impl ProofRequest {
     pub fn new(
-        session: Arc<Mutex<TurboSession<PublicState, PrivateState, GameAction>>>,
+        session_id: String,
         proof_type: ProofType,
-        client: Arc<EnvProver>,
-        elf: Arc<Vec<u8>>,
+        stdin: SP1Stdin,
+        backend: Option<String>,
     ) -> Self {
         Self {
-            session,
+            session_id,
             proof_type,
-            client,
-            elf,
+            stdin,
+            backend,
+        }
+    }
+}
+
+/// How often failed proof jobs are retried, with exponential backoff between
+/// attempts.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_secs(5),
+            max_delay: Duration::from_secs(300),
         }
     }
 }
+
+impl RetryPolicy {
+    /// Delay before the attempt following attempt number `attempt`.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+}
+
+/// Persist `request` with task `task_id` and hand it to the workers.
+pub fn submit_proof_job(
+    queue: &ProveQueue,
+    tx_jobs: &mpsc::UnboundedSender<ProofJob>,
+    task_id: String,
+    request: ProofRequest,
+) -> Result<(), &'static str> {
+    queue.set_request(&task_id, request.clone());
+    tx_jobs
+        .send((task_id, request))
+        .map_err(|_| "Error starting proof job")
+}
+
+/// Requeue every job left queued or running by a previous process.
+pub fn resume_proof_jobs(
+    queue: &ProveQueue,
+    tx_jobs: &mpsc::UnboundedSender<ProofJob>,
+) -> Result<usize, &'static str> {
+    let jobs = queue.pending_jobs();
+    let count = jobs.len();
+
+    for (task_id, request) in jobs {
+        queue.set_status(&task_id, ProveStatus::Queued);
+        tx_jobs
+            .send((task_id, request))
+            .map_err(|_| "Error starting proof job")?;
+    }
+
+    Ok(count)
+}
+
 /// Spawn `num_workers` background tasks that consume `rx_jobs`.
-pub fn spawn_proof_workers<PublicState, PrivateState, GameAction>(
+///
+/// Failed jobs are sent back through `tx_jobs` after the backoff of
+/// `retry_policy`, until they run out of attempts.
+pub fn spawn_proof_workers<PublicState>(
     num_workers: usize,
-    rx_jobs: mpsc::UnboundedReceiver<ProofJob<PublicState, PrivateState, GameAction>>,
+    rx_jobs: mpsc::UnboundedReceiver<ProofJob>,
+    tx_jobs: mpsc::UnboundedSender<ProofJob>,
     queue: Arc<ProveQueue>,
+    router: Arc<ProverRouter>,
+    elf: Arc<Vec<u8>>,
+    artifacts: Arc<dyn ArtifactStore>,
+    retry_policy: RetryPolicy,
 ) where
-    PublicState: Default
+    PublicState: Serialize
         + SolValue
-        + Serialize
         + From<<<PublicState as SolValue>::SolType as alloy_sol_types::SolType>::RustType>
         + Send
         + Sync
         + 'static,
-    PrivateState: Default + Send + Sync + 'static,
-    GameAction: TurboActionSerialization + Send + Sync + 'static,
 {
     let rx = Arc::new(Mutex::new(rx_jobs));
 
     for _ in 0..num_workers {
         let rx = rx.clone();
+        let tx = tx_jobs.clone();
         let queue = queue.clone();
+        let router = router.clone();
+        let elf = elf.clone();
+        let artifacts = artifacts.clone();
+        let retry_policy = retry_policy.clone();
 
         tokio::spawn(async move {
             loop {
@@ -83,20 +147,41 @@ pub fn spawn_proof_workers<PublicState, PrivateState, GameAction>(
                     None => break, // all senders dropped => exit
                 };
 
-                queue.set_status(&task_id, ProveStatus::InProgress);
+                let attempt = queue.start_attempt(&task_id);
+
+                let result = match router.backend_for(&job.proof_type, job.backend.as_deref()) {
+                    Ok(backend) => {
+                        handle_proof_request::<PublicState>(
+                            job.stdin.clone(),
+                            backend,
+                            elf.clone(),
+                            artifacts.clone(),
+                            job.proof_type,
+                            task_id.clone(),
+                        )
+                        .await
+                    }
+                    Err(e) => Err(e),
+                };
 
-                let result = handle_proof_request::<PublicState, PrivateState, GameAction>(
-                    job.session,
-                    job.client,
-                    job.elf,
-                    job.proof_type,
-                    task_id.clone(),
-                )
-                .await
-                .map(ProveStatus::Done)
-                .unwrap_or_else(|e| ProveStatus::Error(e.to_string()));
+                match result {
+                    Ok(reply) => queue.set_status(&task_id, ProveStatus::Done(reply)),
+                    Err(e) if attempt < retry_policy.max_attempts => {
+                        eprintln!(
+                            "Proof {} failed on attempt {}: {}, retrying",
+                            task_id, attempt, e
+                        );
+                        queue.set_status(&task_id, ProveStatus::Queued);
 
-                queue.set_status(&task_id, result);
+                        let tx = tx.clone();
+                        let delay = retry_policy.backoff(attempt);
+                        tokio::spawn(async move {
+                            tokio::time::sleep(delay).await;
+                            let _ = tx.send((task_id, job));
+                        });
+                    }
+                    Err(e) => queue.set_status(&task_id, ProveStatus::Error(e.to_string())),
+                }
             }
         });
     }
@@ -1,8 +1,15 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast;
 use uuid::Uuid;
 
+use crate::proof_worker::ProofRequest;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ProveStatus {
     Queued,
@@ -11,8 +18,71 @@ pub enum ProveStatus {
     Error(String),
 }
 
+impl From<ProveStatus> for turbo_protocol::ProofStatus {
+    fn from(status: ProveStatus) -> Self {
+        match status {
+            ProveStatus::Queued => Self::Queued,
+            ProveStatus::InProgress => Self::InProgress,
+            ProveStatus::Done(proof) => Self::Done { proof },
+            ProveStatus::Error(error) => Self::Error { error },
+        }
+    }
+}
+
+/// A task tracked by the queue: its status, the job to run and how many
+/// times the job has been attempted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProveTask {
+    pub status: ProveStatus,
+    pub request: Option<ProofRequest>,
+    pub attempts: u32,
+    /// Unix time in seconds at which the task reached `Done` or `Error`.
+    #[serde(default)]
+    pub finished_at: Option<u64>,
+}
+
+/// Status change of a task, pushed to subscribers of the queue.
+#[derive(Debug, Clone)]
+pub struct ProofUpdate {
+    pub proof_id: String,
+    pub session_id: Option<String>,
+    pub status: ProveStatus,
+}
+
+/// How long finished tasks are kept around for clients to fetch their result.
+/// `None` disables a limit.
+#[derive(Debug, Clone, Default)]
+pub struct RetentionPolicy {
+    pub max_age: Option<Duration>,
+    pub max_finished: Option<usize>,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// Write `data` to `path` and flush it to disk, so a rename over the task
+/// file after a crash leaves either the old or the new file.
+fn write_synced(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(data)?;
+    file.sync_all()
+}
+
+/// Proof task queue.
+///
+/// When opened on a directory every task is written to `<dir>/<id>.json`, so
+/// statuses and pending jobs survive a restart.
 pub struct ProveQueue {
-    tasks: Arc<Mutex<HashMap<String, ProveStatus>>>,
+    tasks: Arc<Mutex<HashMap<String, ProveTask>>>,
+    dir: Option<PathBuf>,
+    /// Held while touching task files, so a slow write never replaces a
+    /// newer one. Never taken while `tasks` is locked.
+    writes: Mutex<()>,
+    updates: broadcast::Sender<ProofUpdate>,
 }
 
 impl Default for ProveQueue {
@@ -25,24 +95,208 @@ impl ProveQueue {
     pub fn new() -> Self {
         Self {
             tasks: Arc::new(Mutex::new(HashMap::new())),
+            dir: None,
+            writes: Mutex::new(()),
+            updates: broadcast::channel(256).0,
         }
     }
 
+    /// Open a queue persisted in `dir`, loading the tasks saved there. Task
+    /// files that can't be read are renamed to `<id>.json.bad` and skipped.
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self, &'static str> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir).map_err(|_| "Failed to create queue directory")?;
+
+        let mut tasks = HashMap::new();
+        let entries = fs::read_dir(&dir).map_err(|_| "Failed to read queue directory")?;
+        for entry in entries {
+            let path = entry.map_err(|_| "Failed to read queue directory")?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+
+            let task = fs::read(&path)
+                .map_err(|_| "Failed to read task")
+                .and_then(|data| {
+                    serde_json::from_slice::<ProveTask>(&data)
+                        .map_err(|_| "Failed to deserialize task")
+                });
+            match task {
+                Ok(task) => {
+                    tasks.insert(id.to_string(), task);
+                }
+                Err(e) => {
+                    eprintln!("Skipping proof task file {}: {}", path.display(), e);
+                    if fs::rename(&path, path.with_extension("json.bad")).is_err() {
+                        eprintln!("Failed to move aside proof task file {}", path.display());
+                    }
+                }
+            }
+        }
+
+        Ok(Self {
+            tasks: Arc::new(Mutex::new(tasks)),
+            dir: Some(dir),
+            writes: Mutex::new(()),
+            updates: broadcast::channel(256).0,
+        })
+    }
+
+    /// Write the current state of task `id` to its file. The task is read
+    /// again under the write lock, so the last write is always the latest.
+    fn persist(&self, id: &str) {
+        let Some(dir) = &self.dir else {
+            return;
+        };
+
+        let _writes = self.writes.lock().unwrap();
+        let Some(task) = self.tasks.lock().unwrap().get(id).cloned() else {
+            return;
+        };
+
+        let path = dir.join(format!("{}.json", id));
+        let tmp_path = path.with_extension("json.tmp");
+        let result = serde_json::to_vec(&task)
+            .map_err(|e| e.to_string())
+            .and_then(|data| write_synced(&tmp_path, &data).map_err(|e| e.to_string()))
+            .and_then(|_| fs::rename(&tmp_path, &path).map_err(|e| e.to_string()));
+
+        if let Err(e) = result {
+            eprintln!("Failed to persist proof task {}: {}", id, e);
+        }
+    }
+
+    fn update(&self, id: &str, f: impl FnOnce(&mut ProveTask)) -> ProveTask {
+        let task = {
+            let mut tasks = self.tasks.lock().unwrap();
+            let task = tasks.entry(id.to_string()).or_insert(ProveTask {
+                status: ProveStatus::Queued,
+                request: None,
+                attempts: 0,
+                finished_at: None,
+            });
+            f(task);
+            task.clone()
+        };
+        self.persist(id);
+        task
+    }
+
+    fn notify(&self, id: &str, task: ProveTask) {
+        // Nobody listening is fine
+        let _ = self.updates.send(ProofUpdate {
+            proof_id: id.to_string(),
+            session_id: task.request.map(|request| request.session_id),
+            status: task.status,
+        });
+    }
+
+    /// Receive every status change from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<ProofUpdate> {
+        self.updates.subscribe()
+    }
+
     pub fn enqueue_task(&self) -> String {
         let id = Uuid::new_v4().to_string();
-        self.tasks
-            .lock()
-            .unwrap()
-            .insert(id.clone(), ProveStatus::Queued);
+        self.update(&id, |_| {});
         id
     }
 
     pub fn get_status(&self, id: &str) -> Option<ProveStatus> {
-        self.tasks.lock().unwrap().get(id).cloned()
+        self.tasks
+            .lock()
+            .unwrap()
+            .get(id)
+            .map(|task| task.status.clone())
     }
 
     pub fn set_status(&self, id: &String, status: ProveStatus) {
-        self.tasks.lock().unwrap().insert(id.to_string(), status);
+        let task = self.update(id, |task| {
+            task.finished_at = match status {
+                ProveStatus::Done(_) | ProveStatus::Error(_) => Some(unix_now()),
+                ProveStatus::Queued | ProveStatus::InProgress => None,
+            };
+            task.status = status;
+        });
+        self.notify(id, task);
+    }
+
+    /// Attach the job to run for task `id`.
+    pub fn set_request(&self, id: &str, request: ProofRequest) {
+        self.update(id, |task| task.request = Some(request));
+    }
+
+    /// Record a new attempt at task `id`, returning the attempt count.
+    pub fn start_attempt(&self, id: &str) -> u32 {
+        let task = self.update(id, |task| {
+            task.status = ProveStatus::InProgress;
+            task.attempts += 1;
+        });
+        let attempts = task.attempts;
+        self.notify(id, task);
+        attempts
+    }
+
+    /// Jobs which were queued or running, used to resume work after a restart.
+    pub fn pending_jobs(&self) -> Vec<(String, ProofRequest)> {
+        self.tasks
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, task)| {
+                matches!(task.status, ProveStatus::Queued | ProveStatus::InProgress)
+            })
+            .filter_map(|(id, task)| Some((id.clone(), task.request.clone()?)))
+            .collect()
+    }
+
+    /// Sessions referenced by a queued or running job.
+    pub fn active_session_ids(&self) -> HashSet<String> {
+        self.pending_jobs()
+            .into_iter()
+            .map(|(_, request)| request.session_id)
+            .collect()
+    }
+
+    /// Drop finished tasks older than `policy.max_age`, then the oldest ones
+    /// beyond `policy.max_finished`. Returns the ids of the removed tasks.
+    pub fn prune_finished(&self, policy: &RetentionPolicy) -> Vec<String> {
+        let mut tasks = self.tasks.lock().unwrap();
+        let now = unix_now();
+
+        let mut finished: Vec<(String, u64)> = tasks
+            .iter()
+            .filter_map(|(id, task)| Some((id.clone(), task.finished_at?)))
+            .collect();
+        finished.sort_by_key(|(_, finished_at)| std::cmp::Reverse(*finished_at));
+
+        let mut removed = Vec::new();
+        for (i, (id, finished_at)) in finished.into_iter().enumerate() {
+            let too_old = policy
+                .max_age
+                .is_some_and(|max_age| now.saturating_sub(finished_at) > max_age.as_secs());
+            let too_many = policy.max_finished.is_some_and(|max| i >= max);
+            if too_old || too_many {
+                removed.push(id);
+            }
+        }
+
+        for id in &removed {
+            tasks.remove(id);
+        }
+        drop(tasks);
+
+        if let Some(dir) = &self.dir {
+            let _writes = self.writes.lock().unwrap();
+            for id in &removed {
+                let _ = fs::remove_file(dir.join(format!("{}.json", id)));
+            }
+        }
+
+        removed
     }
 }
 
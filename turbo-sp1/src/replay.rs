@@ -0,0 +1,77 @@
+use serde::Serialize;
+use serde_json::Value;
+use turbo_program::{
+    program::TurboReducer,
+    traits::{PlayerView, TurboActionSerialization, TurboInitState},
+};
+
+use crate::session::TurboSession;
+use crate::session_store::SessionRecord;
+
+/// State of a match after one of its actions.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayStep {
+    /// Number of actions applied so far, 0 for the initial state.
+    pub action_count: usize,
+    /// Seat that played the last action, `None` for the initial state.
+    pub player_idx: Option<usize>,
+    /// Hex encoded last action, without its player tag.
+    pub action: Option<String>,
+    /// Spectator view of the state.
+    pub public_state: Value,
+}
+
+/// Step through the action log of `record` with the reducer, returning the
+/// state before the first action and after every action.
+pub fn replay_record<PublicState, PrivateState, GameAction>(
+    reducer: TurboReducer<PublicState, PrivateState, GameAction>,
+    record: SessionRecord,
+) -> Result<Vec<ReplayStep>, &'static str>
+where
+    PublicState: Serialize + Default + TurboInitState + PlayerView<PrivateState> + Send + Sync,
+    PrivateState: Default + Send + Sync,
+    GameAction: TurboActionSerialization + Send + Sync,
+{
+    // Rebuild the seats and seeds alone, then apply the log one action at a time
+    let actions = record.actions.clone();
+    let mut session = TurboSession::restore(
+        reducer,
+        SessionRecord {
+            actions: Vec::new(),
+            is_revealed: false,
+            ..record
+        },
+    )?;
+
+    let mut steps = vec![ReplayStep {
+        action_count: 0,
+        player_idx: None,
+        action: None,
+        public_state: session.spectator_json(),
+    }];
+
+    let mut remaining = &actions[..];
+    while !remaining.is_empty() {
+        if remaining.len() < 2 {
+            return Err("Truncated action log");
+        }
+        let player_idx = remaining[0] as usize;
+        if player_idx >= session.player_count() {
+            return Err("Action for an unknown seat");
+        }
+
+        let (_, next_actions) = GameAction::deserialize(&remaining[1..])?;
+        let action_raw = &remaining[..remaining.len() - next_actions.len()];
+        session.dispatch(action_raw)?;
+
+        steps.push(ReplayStep {
+            action_count: steps.len(),
+            player_idx: Some(player_idx),
+            action: Some(format!("0x{}", hex::encode(&action_raw[1..]))),
+            public_state: session.spectator_json(),
+        });
+        remaining = next_actions;
+    }
+
+    Ok(steps)
+}
@@ -1,21 +1,159 @@
 use alloy_sol_types::SolValue;
-use futures_util::{SinkExt, StreamExt};
-use serde::Serialize;
-use serde_json::{json, Value};
-use std::{convert::Infallible, sync::Arc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{convert::Infallible, sync::Arc, time::Duration};
 use tokio::sync::{mpsc, Mutex};
-use warp::Filter;
+use warp::{Filter, Rejection};
 
-use sp1_sdk::ProverClient;
-use turbo_program::{program::TurboReducer, traits::TurboActionSerialization};
+use turbo_program::{
+    program::TurboReducer,
+    traits::{HasTerminalState, PlayerView, TurboActionSerialization, TurboInitState, UndoRules},
+};
 
+use crate::artifact_store::{ArtifactKind, ArtifactStore, FileArtifactStore};
+use crate::auth::{ChallengeStore, SignedChallenge};
+use crate::backend::{ProverRouter, BACKEND_NAMES};
 use crate::proof::{handle_proof_execute, ProofType};
-use crate::proof_worker::{spawn_proof_workers, ProofJob, ProofRequest};
-use crate::prove_queue::{ProveQueue, ProveStatus};
-use crate::session::TurboSession;
-use crate::session_manager::SessionManager;
-use crate::session_simple::{create_session_json, dispatch_actions};
+use crate::proof_worker::{
+    resume_proof_jobs, spawn_proof_workers, submit_proof_job, ProofJob, ProofRequest, RetryPolicy,
+};
+use crate::prove_queue::{ProveQueue, ProveStatus, RetentionPolicy};
+use crate::replay::replay_record;
+use crate::session::decode_seed;
+use crate::session_manager::{SessionLimits, SessionManager};
+use crate::session_simple::create_session_json;
+use crate::session_store::{FileSessionStore, MemorySessionStore, SessionRecord, SessionStore};
 use crate::warp::rejection::{handle_rejection, ServerError};
+use crate::ws::{handle_socket, WsContext};
+
+/// Prover backends, storage and worker settings of the server.
+pub struct TurboServerOptions {
+    pub num_workers: usize,
+    pub router: ProverRouter,
+    pub session_store: Arc<dyn SessionStore>,
+    pub prove_queue: ProveQueue,
+    /// Where proofs, transcripts and their metadata are kept.
+    pub artifact_store: Arc<dyn ArtifactStore>,
+    pub retry_policy: RetryPolicy,
+    pub session_limits: SessionLimits,
+    pub retention: RetentionPolicy,
+    /// How often expired sessions and proof results are cleaned up.
+    pub sweep_interval: Duration,
+    /// Bearer token of the operator routes, which are disabled without one.
+    pub operator_token: Option<String>,
+}
+
+fn env_u64(name: &str) -> Result<Option<u64>, &'static str> {
+    match std::env::var(name) {
+        Ok(value) => value
+            .parse()
+            .map(Some)
+            .map_err(|_| "Invalid numeric environment variable"),
+        Err(_) => Ok(None),
+    }
+}
+
+fn env_secs(name: &str, default: u64) -> Result<Option<Duration>, &'static str> {
+    // Zero disables the limit
+    let secs = env_u64(name)?.unwrap_or(default);
+    Ok((secs > 0).then_some(Duration::from_secs(secs)))
+}
+
+impl TurboServerOptions {
+    /// Build the options from the environment.
+    ///
+    /// Sessions and proof jobs survive restarts when `TURBO_SESSION_DIR` and
+    /// `TURBO_PROOF_QUEUE_DIR` are set, and are kept in memory otherwise.
+    /// Lifetimes are read in seconds from `TURBO_SESSION_IDLE_SECS`,
+    /// `TURBO_SESSION_MAX_AGE_SECS` and `TURBO_PROOF_RETENTION_SECS`, and
+    /// `TURBO_PROOF_RETENTION_MAX` caps the number of finished proofs kept.
+    /// Proof artifacts are written to `TURBO_ARTIFACT_DIR`, `proofs` by default.
+    /// Operator routes need the bearer token in `TURBO_OPERATOR_TOKEN`.
+    pub fn from_env(num_workers: usize) -> Result<Self, &'static str> {
+        let session_store: Arc<dyn SessionStore> = match std::env::var("TURBO_SESSION_DIR") {
+            Ok(dir) => Arc::new(FileSessionStore::new(dir)?),
+            Err(_) => Arc::new(MemorySessionStore::new()),
+        };
+
+        let prove_queue = match std::env::var("TURBO_PROOF_QUEUE_DIR") {
+            Ok(dir) => ProveQueue::open(dir)?,
+            Err(_) => ProveQueue::new(),
+        };
+
+        let artifact_dir =
+            std::env::var("TURBO_ARTIFACT_DIR").unwrap_or_else(|_| "proofs".to_string());
+
+        Ok(Self {
+            num_workers,
+            router: ProverRouter::from_env()?,
+            session_store,
+            prove_queue,
+            artifact_store: Arc::new(FileArtifactStore::new(artifact_dir)?),
+            retry_policy: RetryPolicy::default(),
+            session_limits: SessionLimits {
+                idle_timeout: env_secs("TURBO_SESSION_IDLE_SECS", 60 * 60)?,
+                max_age: env_secs("TURBO_SESSION_MAX_AGE_SECS", 24 * 60 * 60)?,
+            },
+            retention: RetentionPolicy {
+                max_age: env_secs("TURBO_PROOF_RETENTION_SECS", 24 * 60 * 60)?,
+                max_finished: Some(env_u64("TURBO_PROOF_RETENTION_MAX")?.unwrap_or(1000) as usize),
+            },
+            sweep_interval: Duration::from_secs(60),
+            operator_token: std::env::var("TURBO_OPERATOR_TOKEN")
+                .ok()
+                .filter(|token| !token.is_empty()),
+        })
+    }
+}
+
+/// Body of `POST /session/{id}/join`.
+#[derive(Deserialize)]
+struct JoinRequest {
+    #[serde(flatten)]
+    auth: SignedChallenge,
+    /// Hex encoded BN254 G1 point, the server picks one when missing.
+    #[serde(default)]
+    seed: Option<String>,
+}
+
+/// Query of `POST /prove/{proof_type}`.
+#[derive(Deserialize)]
+struct ProveQuery {
+    /// Backend to prove with instead of the one configured for the proof type.
+    #[serde(default)]
+    backend: Option<String>,
+}
+
+/// Compare without an early exit, so response times don't leak the token.
+fn token_matches(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Requests carrying `Authorization: Bearer <token>`. Without a token the
+/// routes behind this filter don't exist.
+fn operator_auth(
+    token: Option<Arc<String>>,
+) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::header::optional::<String>("authorization")
+        .and_then(move |header: Option<String>| {
+            let token = token.clone();
+            async move {
+                let Some(token) = token else {
+                    return Err(warp::reject::not_found());
+                };
+                match header.as_deref().and_then(|h| h.strip_prefix("Bearer ")) {
+                    Some(given) if token_matches(given, &token) => Ok(()),
+                    _ => Err(ServerError::unauthorized("Operator token required".into())),
+                }
+            }
+        })
+        .untuple_one()
+}
 
 pub fn turbo_sp1_routes<PublicState, PrivateState, GameAction>(
     elf: &[u8],
@@ -24,42 +162,130 @@ pub fn turbo_sp1_routes<PublicState, PrivateState, GameAction>(
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Infallible> + Clone
 where
     PublicState: Default
+        + TurboInitState
+        + PlayerView<PrivateState>
+        + UndoRules
+        + HasTerminalState
+        + Clone
+        + SolValue
+        + Serialize
+        + From<<<PublicState as SolValue>::SolType as alloy_sol_types::SolType>::RustType>
+        + Send
+        + Sync
+        + 'static,
+    PrivateState: Default + Serialize + Clone + Send + Sync + 'static,
+    GameAction: TurboActionSerialization + Send + Sync + 'static,
+{
+    let options = TurboServerOptions::from_env(num_workers).expect("Failed to configure server");
+    turbo_sp1_routes_with_options(elf, reducer, options)
+}
+
+/// Same as `turbo_sp1_routes` with explicit `options`. Sessions saved in the
+/// session store are restored and pending proof jobs are resumed.
+pub fn turbo_sp1_routes_with_options<PublicState, PrivateState, GameAction>(
+    elf: &[u8],
+    reducer: TurboReducer<PublicState, PrivateState, GameAction>,
+    options: TurboServerOptions,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Infallible> + Clone
+where
+    PublicState: Default
+        + TurboInitState
+        + PlayerView<PrivateState>
+        + UndoRules
+        + HasTerminalState
+        + Clone
         + SolValue
         + Serialize
         + From<<<PublicState as SolValue>::SolType as alloy_sol_types::SolType>::RustType>
         + Send
         + Sync
         + 'static,
-    PrivateState: Default + Serialize + Send + Sync + 'static,
+    PrivateState: Default + Serialize + Clone + Send + Sync + 'static,
     GameAction: TurboActionSerialization + Send + Sync + 'static,
 {
-    let client_arc = Arc::new(ProverClient::from_env());
+    let router_arc = Arc::new(options.router);
     let elf_arc = Arc::new(elf.to_vec());
-    let prove_queue_arc = Arc::new(ProveQueue::new());
-    let session_manager_arc = Arc::new(Mutex::new(SessionManager::new()));
-    let (tx_jobs, rx_jobs) =
-        mpsc::unbounded_channel::<ProofJob<PublicState, PrivateState, GameAction>>();
-    let tx_jobs_arc = Arc::new(tx_jobs);
+    let prove_queue_arc = Arc::new(options.prove_queue);
+    let mut session_manager =
+        SessionManager::with_store(options.session_store).with_limits(options.session_limits);
+    let restored = session_manager
+        .restore_sessions(reducer)
+        .expect("Failed to restore sessions");
+    if restored > 0 {
+        println!("Restored {} sessions", restored);
+    }
+    let session_manager_arc = Arc::new(Mutex::new(session_manager));
+    let operator_token = options.operator_token.map(Arc::new);
+    let challenges = Arc::new(ChallengeStore::new());
+    let (tx_jobs, rx_jobs) = mpsc::unbounded_channel::<ProofJob>();
 
-    spawn_proof_workers::<PublicState, PrivateState, GameAction>(
-        num_workers,
+    let resumed =
+        resume_proof_jobs(&prove_queue_arc, &tx_jobs).expect("Failed to resume proof jobs");
+    if resumed > 0 {
+        println!("Resumed {} proof jobs", resumed);
+    }
+
+    spawn_proof_workers::<PublicState>(
+        options.num_workers,
         rx_jobs,
+        tx_jobs.clone(),
         prove_queue_arc.clone(),
+        router_arc.clone(),
+        elf_arc.clone(),
+        options.artifact_store.clone(),
+        options.retry_policy,
     );
+    let tx_jobs_arc = Arc::new(tx_jobs);
+
+    // Periodically evict expired sessions and old proof results. Sessions with
+    // a queued or running proof are never evicted. Artifacts go along with
+    // their proof result.
+    let sweep_session_manager = session_manager_arc.clone();
+    let sweep_queue = prove_queue_arc.clone();
+    let sweep_artifacts = options.artifact_store.clone();
+    let sweep_interval = options.sweep_interval;
+    let retention = options.retention;
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(sweep_interval);
+        loop {
+            interval.tick().await;
+
+            let pinned = sweep_queue.active_session_ids();
+            let evicted = sweep_session_manager
+                .lock()
+                .await
+                .evict_expired(&pinned)
+                .await;
+            let pruned = sweep_queue.prune_finished(&retention);
+            for proof_id in &pruned {
+                if let Err(e) = sweep_artifacts.remove(proof_id) {
+                    eprintln!("Failed to remove artifacts of proof {}: {}", proof_id, e);
+                }
+            }
 
-    let execute_client = client_arc.clone();
+            if !evicted.is_empty() || !pruned.is_empty() {
+                println!(
+                    "Evicted {} sessions, pruned {} proof results",
+                    evicted.len(),
+                    pruned.len()
+                );
+            }
+        }
+    });
+
+    let execute_router = router_arc.clone();
     let execute_elf = elf_arc.clone();
     let execute_session_manager = session_manager_arc.clone();
     let execute_route = warp::path!("execute")
         .and(warp::post())
         .and(warp::body::json())
         .and_then(move |actions: serde_json::Value| {
-            let client = execute_client.clone();
+            let client = execute_router.default_backend();
             let elf = execute_elf.clone();
             let session_manager = execute_session_manager.clone();
 
             async move {
-                let session = {
+                let (session_id, session) = {
                     let mut session_manager_guard = session_manager.lock().await;
                     let session_id =
                         match create_session_json(&mut session_manager_guard, reducer, actions)
@@ -69,43 +295,52 @@ where
                             Err(err) => return Err(ServerError::bad_request(err.to_string())),
                         };
                     match session_manager_guard.get_session(&session_id).await {
-                        Some(session) => session,
+                        Some(session) => (session_id, session),
                         None => {
                             return Err(ServerError::bad_request("Failed to get session".into()))
                         }
                     }
                 };
 
-                handle_proof_execute::<PublicState, PrivateState, GameAction>(session, client, elf)
+                let result = handle_proof_execute::<PublicState, PrivateState, GameAction>(
+                    session, client, elf,
+                )
+                .await;
+
+                // The session only lives for this request
+                let _ = session_manager
+                    .lock()
                     .await
+                    .close_session(&session_id)
+                    .await;
+
+                result
                     .map(|reply| warp::reply::json(&reply))
                     .map_err(|e| ServerError::bad_request(e.to_string()))
             }
         });
 
-    let prove_client = client_arc.clone();
-    let prove_elf = elf_arc.clone();
     let prove_queue = prove_queue_arc.clone();
     let prove_session_manager = session_manager_arc.clone();
     let prove_tx_jobs = tx_jobs_arc.clone();
     let prove_route = warp::path!("prove" / String)
         .and(warp::post())
-        .and(warp::body::json())
-        .and_then(move |proof_type: String, actions: serde_json::Value| {
-            let client = prove_client.clone();
-            let elf = prove_elf.clone();
+        .and(warp::query::<ProveQuery>())
+        .and(warp::body::json::<serde_json::Value>())
+        .and_then(move |proof_type: String, query: ProveQuery, actions| {
             let queue = prove_queue.clone();
             let session_manager = prove_session_manager.clone();
             let tx_jobs = prove_tx_jobs.clone();
 
             async move {
-                let proof_type = match proof_type.as_str() {
-                    "core" => ProofType::Core,
-                    "compressed" => ProofType::Compressed,
-                    "groth16" => ProofType::Groth16,
-                    "plonk" => ProofType::Plonk,
-                    _ => return Err(ServerError::bad_request("Invalid proof type".into())),
-                };
+                let proof_type = proof_type
+                    .parse::<ProofType>()
+                    .map_err(|e| ServerError::bad_request(e.to_string()))?;
+                if let Some(backend) = &query.backend {
+                    if !BACKEND_NAMES.contains(&backend.as_str()) {
+                        return Err(ServerError::bad_request("Unknown prover backend".into()));
+                    }
+                }
 
                 // Create a new task in the queue
                 let task_id = queue.enqueue_task();
@@ -135,20 +370,17 @@ where
                     ));
                 }
 
-                // Start a new proof job
-                tx_jobs
-                    .send((
-                        task_id_clone,
-                        ProofRequest::new(
-                            session_option.unwrap(),
-                            proof_type,
-                            client.clone(),
-                            elf.clone(),
-                        ),
-                    ))
-                    .map_err(|_| {
-                        ServerError::internal_server_error("Error starting proof job".into())
-                    })?;
+                // Start a new proof job. The job carries its own stdin, so the
+                // session is not needed past this request.
+                let stdin = session_option.unwrap().lock().await.sp1_stdin();
+                let _ = session_manager_guard.close_session(&session_id).await;
+                submit_proof_job(
+                    &queue,
+                    &tx_jobs,
+                    task_id_clone,
+                    ProofRequest::new(session_id, proof_type, stdin, query.backend),
+                )
+                .map_err(|e| ServerError::internal_server_error(e.to_string()))?;
 
                 // Return the task ID to the client
                 Ok(warp::reply::json(&json!({
@@ -187,177 +419,319 @@ where
                 }
             });
 
-    // Add a WebSocket route for processing commands
-    let ws_client = client_arc.clone();
-    let ws_elf = elf_arc.clone();
-    let ws_prove_queue = prove_queue_arc.clone();
-    let ws_session_manager = session_manager_arc.clone();
-    let ws_tx_jobs = tx_jobs_arc.clone();
-    let ws_route = warp::path("ws")
-        .and(warp::ws())
-        .and_then(move |ws: warp::ws::Ws| {
-            let session_manager = ws_session_manager.clone();
-            let prove_queue = ws_prove_queue.clone();
-            let client = ws_client.clone();
-            let elf = ws_elf.clone();
-            let tx_jobs = ws_tx_jobs.clone();
-            
+    // Add routes to download the proof of a finished task and to read its
+    // metadata
+    let artifact_store = options.artifact_store.clone();
+    let artifact_route = warp::path!("proof" / String / "artifact")
+        .and(warp::get())
+        .and_then(move |proof_id: String| {
+            let artifacts = artifact_store.clone();
+            async move {
+                match artifacts
+                    .get(&proof_id, ArtifactKind::Proof)
+                    .map_err(|e| ServerError::bad_request(e.into()))?
+                {
+                    Some(data) => Ok(warp::http::Response::builder()
+                        .header("content-type", "application/octet-stream")
+                        .header(
+                            "content-disposition",
+                            format!("attachment; filename=\"{}.bin\"", proof_id),
+                        )
+                        .body(data)
+                        .map_err(|_| {
+                            ServerError::internal_server_error("Failed to build response".into())
+                        })?),
+                    None => Err(ServerError::not_found("Proof artifact not found".into())),
+                }
+            }
+        });
+
+    let metadata_store = options.artifact_store.clone();
+    let metadata_route = warp::path!("proof" / String / "metadata")
+        .and(warp::get())
+        .and_then(move |proof_id: String| {
+            let artifacts = metadata_store.clone();
+            async move {
+                match artifacts
+                    .get_metadata(&proof_id)
+                    .map_err(|e| ServerError::bad_request(e.into()))?
+                {
+                    Some(metadata) => Ok(warp::reply::json(&metadata)),
+                    None => Err(ServerError::not_found("Proof artifact not found".into())),
+                }
+            }
+        });
+
+    // Add a route to fetch the server seed commitment of a session
+    let commitment_session_manager = session_manager_arc.clone();
+    let commitment_route = warp::path!("session" / String / "commitment")
+        .and(warp::get())
+        .and_then(move |session_id: String| {
+            let session_manager = commitment_session_manager.clone();
             async move {
-                Ok::<_, warp::reject::Rejection>(ws.on_upgrade(move |websocket| async move {
-                    let (mut tx, mut rx) = websocket.split();
-                    let session_manager = session_manager.clone();
-                    let mut active_session: Option<Arc<Mutex<TurboSession<PublicState, PrivateState, GameAction>>>> = None;
-                    let mut active_proof_id: Option<String> = None;
-                    let mut active_player_idx: Option<usize> = None;
-
-                    if let Err(_) = tx.send(warp::ws::Message::text("{\"__state\":\"waiting\"}")).await {
-                        return;
+                let session = session_manager.lock().await.get_session(&session_id).await;
+                match session {
+                    Some(session) => {
+                        let server_commitment = session.lock().await.server_commitment();
+                        Ok(warp::reply::json(&json!({
+                            "session_id": session_id,
+                            "server_commitment": format!("0x{}", hex::encode(server_commitment)),
+                        })))
                     }
+                    None => Err(ServerError::not_found("Session not found".into())),
+                }
+            }
+        });
 
-                    // Process messages one by one
-                    while let Some(result) = rx.next().await {
-                        match result {
-                            Ok(msg) => {
-                                if let Ok(text) = msg.to_str() {
-                                    // Parse the JSON command
-                                    match serde_json::from_str::<serde_json::Value>(text) {
-                                        Ok(command) => {
-                                            let response = if command.get("__syscall").is_some() {
-                                                let syscall = command.get("__syscall").unwrap().as_str().unwrap();
-                                                let mut response: Option<Value> = None;
-
-                                                if syscall == "join_session" {
-                                                    let session_id_option = command.get("session_id");
-                                                    let session_id = match session_id_option {
-                                                        Some(session_id) => session_id.as_str().unwrap(),
-                                                        None => &session_manager.lock().await.create_session(reducer).await
-                                                    };
-                                                    
-                                                    let session_option = session_manager.lock().await.get_session(session_id).await;
-                                                            
-                                                    if session_option.is_some() {
-                                                        let session = session_option.unwrap();
-                                                        let player_idx = session.lock().await.join_random();
-
-                                                        active_session = Some(session);
-                                                        active_player_idx = Some(player_idx);
-
-                                                        response = Some(json!({
-                                                            "__state": "ready",
-                                                            "__session_id": session_id.to_string(),
-                                                        }));
-                                                    } else {
-                                                        response = Some(json!({
-                                                            "error": "Failed to create session"
-                                                        }));
-                                                    }
-                                                } else if syscall == "proof" {
-                                                    let proof_type = command.get("proof_type").unwrap().as_str().unwrap();
-                                                    let proof_type = match proof_type {
-                                                        "core" => ProofType::Core,
-                                                        "compressed" => ProofType::Compressed,
-                                                        "groth16" => ProofType::Groth16,
-                                                        "plonk" => ProofType::Plonk,
-                                                        _ => panic!("Invalid proof type"),
-                                                    };
-                                    
-                                                    // Create a new task in the queue
-                                                    let proof_id = prove_queue.enqueue_task();
-                                                    let proof_id_clone = proof_id.clone();
-
-                                                    // Set active proof id
-                                                    active_proof_id = Some(proof_id.clone());
-
-                                                    // Start a new proof job
-                                                    tx_jobs
-                                                        .send((
-                                                            proof_id_clone,
-                                                            ProofRequest::new(
-                                                                active_session.clone().unwrap().clone(),
-                                                                proof_type,
-                                                                client.clone(),
-                                                                elf.clone(),
-                                                            ),
-                                                        )).unwrap();
-
-                                                    response = Some(json!({
-                                                        "proof_id": proof_id.clone(),
-                                                    }));
-                                                } else if syscall == "proof_status" {
-                                                    let proof_id_option = command.get("proof_id");
-                                                    let proof_id: String = match proof_id_option {
-                                                        Some(proof_id) => proof_id.as_str().unwrap().to_string(),
-                                                        None => active_proof_id.clone().unwrap(),
-                                                    };
-                                                    
-                                                    let status = prove_queue.get_status(&proof_id);
-                                                    let status = match status {
-                                                        Some(status) => status,
-                                                        None => ProveStatus::Error("Proof not found".into()),
-                                                    };
-                                                    
-                                                    response = match status {
-                                                        ProveStatus::Queued => Some(json!({
-                                                            "proof_id": proof_id,
-                                                            "status": "queued"
-                                                        })),
-                                                        ProveStatus::InProgress => Some(json!({
-                                                            "proof_id": proof_id,
-                                                            "status": "in_progress"
-                                                        })),
-                                                        ProveStatus::Done(result) => Some(json!({
-                                                            "proof_id": proof_id,
-                                                            "status": "done",
-                                                            "proof": result
-                                                        })),
-                                                        ProveStatus::Error(error) => Some(json!({
-                                                            "proof_id": proof_id,
-                                                            "status": "error",
-                                                            "error": error
-                                                        })),
-                                                    };
-                                                }
-
-                                                // Handle syscall
-                                                serde_json::to_string(&response.unwrap_or(json!({
-                                                    "error": "Syscalls not yet implemented"
-                                                }))).unwrap_or_else(|_| String::from("{\"error\":\"Failed to serialize response\"}"))
-                                            } else {
-                                                let player_idx = active_player_idx.unwrap();
-                                                let result = dispatch_actions(active_session.clone().unwrap(), command, player_idx).await;
-
-                                                if let Err(e) = result {
-                                                    serde_json::to_string(&json!({
-                                                        "error": e
-                                                    })).unwrap_or_else(|_| String::from("{\"error\":\"Failed to serialize response\"}"))
-                                                } else {
-                                                    let result_json = active_session.clone().unwrap().lock().await.serialize_json(player_idx).unwrap();
-                                                    serde_json::to_string(&result_json).unwrap_or_else(|_| String::from("{\"error\":\"Failed to serialize response\"}"))
-                                                }
-                                            };
-
-                                            if let Err(_) = tx.send(warp::ws::Message::text(response)).await {
-                                                break;
-                                            }
-                                        },
-                                        Err(_) => {
-                                            if let Err(_) = tx.send(warp::ws::Message::text("{\"error\":\"Invalid JSON\"}")).await {
-                                                break;
-                                            }
-                                        }
-                                    }
-                                }
-                            },
-                            Err(_) => break
-                        }
+    // Add a route to get a challenge to sign, proving the address of the
+    // player in the requests that need one
+    let challenge_store = challenges.clone();
+    let challenge_route = warp::path!("auth" / "challenge")
+        .and(warp::get())
+        .and_then(move || {
+            let challenges = challenge_store.clone();
+            async move {
+                let challenge = challenges
+                    .issue()
+                    .map_err(|e| ServerError::internal_server_error(e.into()))?;
+                Ok::<_, Rejection>(warp::reply::json(&json!({ "challenge": challenge })))
+            }
+        });
+
+    // Add a route to join a session with a player seed. The seat is bound to
+    // the address that signed the challenge, as over WebSocket.
+    let join_session_manager = session_manager_arc.clone();
+    let join_challenges = challenges.clone();
+    let join_route = warp::path!("session" / String / "join")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and_then(move |session_id: String, request: JoinRequest| {
+            let session_manager = join_session_manager.clone();
+            let challenges = join_challenges.clone();
+            async move {
+                let address = challenges
+                    .verify(&request.auth)
+                    .map_err(|e| ServerError::unauthorized(e.into()))?;
+                let seed = request
+                    .seed
+                    .map(|seed| decode_seed(&seed))
+                    .transpose()
+                    .map_err(|e| ServerError::bad_request(e.into()))?;
+
+                let session = session_manager.lock().await.get_session(&session_id).await;
+                let Some(session) = session else {
+                    return Err(ServerError::not_found("Session not found".into()));
+                };
+
+                let mut session_guard = session.lock().await;
+                let player_idx = session_guard
+                    .join_as(address, seed)
+                    .map_err(|e| ServerError::bad_request(e.to_string()))?;
+                let server_commitment = session_guard.server_commitment();
+                let seed_commitment = session_guard
+                    .seed_commitment(player_idx)
+                    .unwrap_or_default();
+
+                Ok(warp::reply::json(&json!({
+                    "session_id": session_id,
+                    "player_idx": player_idx,
+                    "server_commitment": format!("0x{}", hex::encode(server_commitment)),
+                    "seed_commitment": format!("0x{}", hex::encode(seed_commitment)),
+                })))
+            }
+        });
+
+    // Add operator routes to checkpoint, roll back and fork sessions. Rollback
+    // and fork take the number of actions to keep.
+    let checkpoint_session_manager = session_manager_arc.clone();
+    let checkpoint_route = warp::path!("session" / String / "checkpoint")
+        .and(warp::post())
+        .and(operator_auth(operator_token.clone()))
+        .and_then(move |session_id: String| {
+            let session_manager = checkpoint_session_manager.clone();
+            async move {
+                let session = session_manager.lock().await.get_session(&session_id).await;
+                let Some(session) = session else {
+                    return Err(ServerError::not_found("Session not found".into()));
+                };
+
+                let action_count = session
+                    .lock()
+                    .await
+                    .checkpoint()
+                    .map_err(|e| ServerError::bad_request(e.to_string()))?;
+                Ok(warp::reply::json(&json!({
+                    "session_id": session_id,
+                    "action_count": action_count,
+                })))
+            }
+        });
+
+    let rollback_session_manager = session_manager_arc.clone();
+    let rollback_route = warp::path!("session" / String / "rollback" / usize)
+        .and(warp::post())
+        .and(operator_auth(operator_token.clone()))
+        .and_then(move |session_id: String, action_count: usize| {
+            let session_manager = rollback_session_manager.clone();
+            async move {
+                let session = session_manager.lock().await.get_session(&session_id).await;
+                let Some(session) = session else {
+                    return Err(ServerError::not_found("Session not found".into()));
+                };
+
+                session
+                    .lock()
+                    .await
+                    .rollback(action_count)
+                    .map_err(|e| ServerError::bad_request(e.to_string()))?;
+                Ok(warp::reply::json(&json!({
+                    "session_id": session_id,
+                    "action_count": action_count,
+                })))
+            }
+        });
+
+    let fork_session_manager = session_manager_arc.clone();
+    let fork_route = warp::path!("session" / String / "fork" / usize)
+        .and(warp::post())
+        .and(operator_auth(operator_token.clone()))
+        .and_then(move |session_id: String, action_count: usize| {
+            let session_manager = fork_session_manager.clone();
+            async move {
+                match session_manager
+                    .lock()
+                    .await
+                    .fork_session(&session_id, action_count)
+                    .await
+                {
+                    Ok(fork_id) => Ok(warp::reply::json(&json!({
+                        "session_id": fork_id,
+                        "forked_from": session_id,
+                        "action_count": action_count,
+                    }))),
+                    Err("Session not found") => {
+                        Err(ServerError::not_found("Session not found".into()))
                     }
-                }))
+                    Err(e) => Err(ServerError::bad_request(e.to_string())),
+                }
+            }
+        });
+
+    // Add routes to replay a match one action at a time, from a session or
+    // from an exported record
+    let replay_session_manager = session_manager_arc.clone();
+    let replay_session_route = warp::path!("session" / String / "replay")
+        .and(warp::get())
+        .and_then(move |session_id: String| {
+            let session_manager = replay_session_manager.clone();
+            async move {
+                let record = session_manager
+                    .lock()
+                    .await
+                    .load_record(&session_id)
+                    .await
+                    .map_err(|e| ServerError::internal_server_error(e.to_string()))?
+                    .ok_or_else(|| ServerError::not_found("Session not found".into()))?;
+
+                replay_record(reducer, record)
+                    .map(|steps| warp::reply::json(&steps))
+                    .map_err(|e| ServerError::bad_request(e.to_string()))
             }
         });
 
+    let replay_route = warp::path!("replay")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and_then(move |record: SessionRecord| async move {
+            replay_record(reducer, record)
+                .map(|steps| warp::reply::json(&steps))
+                .map_err(|e| ServerError::bad_request(e.to_string()))
+        });
+
+    // Add a route to reveal the server seed once the game is over. The body
+    // is a challenge signed by a player with a seat in the session.
+    let reveal_session_manager = session_manager_arc.clone();
+    let reveal_challenges = challenges.clone();
+    let reveal_route = warp::path!("session" / String / "reveal")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and_then(move |session_id: String, auth: SignedChallenge| {
+            let session_manager = reveal_session_manager.clone();
+            let challenges = reveal_challenges.clone();
+            async move {
+                let address = challenges
+                    .verify(&auth)
+                    .map_err(|e| ServerError::unauthorized(e.into()))?;
+
+                let session = session_manager.lock().await.get_session(&session_id).await;
+                let Some(session) = session else {
+                    return Err(ServerError::not_found("Session not found".into()));
+                };
+
+                let mut session_guard = session.lock().await;
+                if session_guard.seat_of(&address).is_none() {
+                    return Err(ServerError::forbidden("Address has no seat".into()));
+                }
+                session_guard
+                    .reveal()
+                    .map(|reveal| warp::reply::json(&reveal))
+                    .map_err(|e| ServerError::bad_request(e.into()))
+            }
+        });
+
+    // Add an operator route to close a session for every player
+    let close_session_manager = session_manager_arc.clone();
+    let close_route = warp::path!("session" / String)
+        .and(warp::delete())
+        .and(operator_auth(operator_token.clone()))
+        .and_then(move |session_id: String| {
+            let session_manager = close_session_manager.clone();
+            async move {
+                match session_manager
+                    .lock()
+                    .await
+                    .close_session(&session_id)
+                    .await
+                {
+                    Ok(true) => Ok(warp::reply::json(&json!({
+                        "session_id": session_id,
+                        "status": "closed"
+                    }))),
+                    Ok(false) => Err(ServerError::not_found("Session not found".into())),
+                    Err(e) => Err(ServerError::internal_server_error(e.to_string())),
+                }
+            }
+        });
+
+    // Add a WebSocket route speaking the typed protocol of `turbo_protocol`
+    let ws_context = WsContext {
+        reducer,
+        session_manager: session_manager_arc.clone(),
+        prove_queue: prove_queue_arc.clone(),
+        tx_jobs: tx_jobs_arc.clone(),
+    };
+    let ws_route = warp::path("ws")
+        .and(warp::ws())
+        .map(move |ws: warp::ws::Ws| {
+            let ctx = ws_context.clone();
+            ws.on_upgrade(move |websocket| handle_socket(websocket, ctx))
+        });
+
     execute_route
         .or(prove_route)
         .or(prove_result_route)
+        .or(artifact_route)
+        .or(metadata_route)
+        .or(commitment_route)
+        .or(challenge_route)
+        .or(join_route)
+        .or(replay_session_route)
+        .or(replay_route)
+        .or(checkpoint_route)
+        .or(rollback_route)
+        .or(fork_route)
+        .or(reveal_route)
+        .or(close_route)
         .or(ws_route)
         .recover(handle_rejection)
 }
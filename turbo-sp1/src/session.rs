@@ -1,19 +1,48 @@
+use std::collections::HashSet;
 use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+use std::time::Instant;
 
-use rand::thread_rng;
+use rand::{thread_rng, Rng};
 use serde::Serialize;
 use serde_json::{json, Value};
 use sp1_sdk::SP1Stdin;
 use substrate_bn::*;
+use tokio::sync::broadcast;
 use turbo_program::{
     context::{TurboActionContext, TurboActionContextInner},
-    crypto::bn_serialize::bn254_export_affine_g1_memcpy,
+    crypto::bn_serialize::{bn254_export_affine_g1_memcpy, bn254_try_import_affine_g1},
     metadata::{PlayerMetadata, ServerMetadata},
     program::TurboReducer,
-    traits::TurboActionSerialization,
+    rand::provably_fair::{commit_combined_seed, commit_seed, is_valid_seed, FairnessReveal},
+    traits::{HasTerminalState, PlayerView, TurboActionSerialization, TurboInitState, UndoRules},
 };
 use uuid::Uuid;
 
+use crate::session_store::{SessionRecord, SessionStore};
+
+/// Change notifications sent to every subscriber of a session.
+#[derive(Debug, Clone)]
+pub enum SessionEvent {
+    /// `player_idx` dispatched an action.
+    Dispatched {
+        player_idx: usize,
+    },
+    /// The session went back to the state after its first `action_count` actions.
+    RolledBack {
+        action_count: usize,
+    },
+    Closed,
+}
+
+/// Game state after the first `action_count` actions of the log.
+struct SessionSnapshot<PublicState, PrivateState> {
+    action_count: usize,
+    public_state: PublicState,
+    private_state: PrivateState,
+    contexts: Vec<TurboActionContextInner>,
+}
+
 pub struct TurboSession<PublicState, PrivateState, GameAction>
 where
     PublicState: Serialize + Default + Send + Sync,
@@ -22,6 +51,8 @@ where
 {
     id: String,
     actions: Vec<u8>,
+    /// End offset in `actions` of every logged action.
+    action_ends: Vec<usize>,
     server_metadata: ServerMetadata,
     player_metadata: Vec<PlayerMetadata>,
     contexts: Vec<TurboActionContextInner>,
@@ -29,37 +60,178 @@ where
     reducer: TurboReducer<PublicState, PrivateState, GameAction>,
     public_state: PublicState,
     private_state: PrivateState,
+    snapshots: Vec<SessionSnapshot<PublicState, PrivateState>>,
+    /// Seats whose players left the session.
+    left_seats: HashSet<usize>,
 
     is_bricked: bool,
+    is_revealed: bool,
+
+    store: Option<Arc<dyn SessionStore>>,
+    created_at: Instant,
+    last_active: Instant,
+    events: broadcast::Sender<SessionEvent>,
+}
+
+/// Decode a hex encoded BN254 G1 point (big-endian `x || y`) into seed words.
+pub fn decode_seed(seed: &str) -> Result<[u32; 16], &'static str> {
+    let bytes = hex::decode(seed.trim_start_matches("0x")).map_err(|_| "Invalid seed encoding")?;
+    let bytes: [u8; 64] = bytes.try_into().map_err(|_| "Seed must be 64 bytes")?;
+    let point = bn254_try_import_affine_g1(&bytes)?;
+    Ok(bn254_export_affine_g1_memcpy(&point))
+}
+
+fn random_player_metadata(address: [u8; 20]) -> PlayerMetadata {
+    let mut rng = thread_rng();
+    let player_random_seed = AffineG1::one() * Fr::random(&mut rng);
+    PlayerMetadata {
+        random_seed: bn254_export_affine_g1_memcpy(&player_random_seed),
+        address,
+    }
 }
 
 impl<
-        PublicState: Serialize + Default + Send + Sync,
+        PublicState: Serialize + Default + TurboInitState + Send + Sync,
         PrivateState: Default + Send + Sync,
         GameAction: TurboActionSerialization + Send + Sync,
     > TurboSession<PublicState, PrivateState, GameAction>
 {
     pub fn new(reducer: TurboReducer<PublicState, PrivateState, GameAction>) -> Self {
+        // An empty config always gives the default game setup
+        Self::new_with_config(reducer, Vec::new()).expect("Invalid default game config")
+    }
+
+    pub fn new_with_config(
+        reducer: TurboReducer<PublicState, PrivateState, GameAction>,
+        game_config: Vec<u8>,
+    ) -> Result<Self, &'static str> {
         let id = Uuid::new_v4().to_string();
         let mut rng = thread_rng();
 
         let server_random_seed = AffineG1::one() * Fr::random(&mut rng);
+        let public_state = PublicState::init_state(&game_config)?;
 
-        Self {
+        Ok(Self {
             id,
             actions: Vec::new(),
+            action_ends: Vec::new(),
             server_metadata: ServerMetadata {
                 random_seed: bn254_export_affine_g1_memcpy(&server_random_seed),
+                match_nonce: rng.gen(),
+                game_config,
             },
             player_metadata: Vec::new(),
             contexts: Vec::new(),
             reducer,
-            public_state: PublicState::default(),
+            public_state,
             private_state: PrivateState::default(),
+            snapshots: Vec::new(),
+            left_seats: HashSet::new(),
             is_bricked: false,
+            is_revealed: false,
+            store: None,
+            created_at: Instant::now(),
+            last_active: Instant::now(),
+            events: broadcast::channel(64).0,
+        })
+    }
+
+    /// Rebuild a session from its record by replaying the action log through
+    /// the reducer, exactly as `dispatch` applied it the first time.
+    pub fn restore(
+        reducer: TurboReducer<PublicState, PrivateState, GameAction>,
+        record: SessionRecord,
+    ) -> Result<Self, &'static str> {
+        let mut session =
+            Self::new_with_config(reducer, record.server_metadata.game_config.clone())?;
+        session.id = record.id;
+        session.server_metadata = record.server_metadata;
+
+        for player_metadata in record.player_metadata {
+            session.join(player_metadata)?;
+        }
+
+        let mut remaining = &record.actions[..];
+        while !remaining.is_empty() {
+            let (_, next_actions) = GameAction::deserialize(&remaining[1..])?;
+            let action_len = remaining.len() - next_actions.len();
+
+            session.apply(&remaining[..action_len])?;
+            remaining = next_actions;
+        }
+
+        session.is_revealed = record.is_revealed;
+        Ok(session)
+    }
+
+    /// Fail once every seat the game gives turns to is taken.
+    fn check_free_seat(&self) -> Result<(), &'static str> {
+        match self.public_state.max_players() {
+            Some(max_players) if self.player_metadata.len() >= max_players => {
+                Err("Session is full")
+            }
+            _ => Ok(()),
         }
     }
 
+    /// Take a new seat. A seat with a non-zero address is bound to it, and an
+    /// address can own a single seat per session.
+    pub fn join(&mut self, player_metadata: PlayerMetadata) -> Result<usize, &'static str> {
+        if player_metadata.address != [0; 20] && self.seat_of(&player_metadata.address).is_some() {
+            return Err("Address already owns a seat");
+        }
+        self.check_free_seat()?;
+
+        Ok(self.push_player(player_metadata))
+    }
+
+    /// Seat owned by `address`, taking a new one if it has none yet. The new
+    /// seat uses the player `seed`, or a random one. The zero address always
+    /// takes an anonymous seat.
+    pub fn join_as(
+        &mut self,
+        address: [u8; 20],
+        seed: Option<[u32; 16]>,
+    ) -> Result<usize, &'static str> {
+        if seed.is_some_and(|seed| !is_valid_seed(&seed)) {
+            return Err("Seed is not a point on the curve");
+        }
+
+        if address != [0; 20] {
+            if let Some(player_idx) = self.seat_of(&address) {
+                let random_seed = self.player_metadata[player_idx].random_seed;
+                if seed.is_some_and(|seed| seed != random_seed) {
+                    return Err("Address already joined with another seed");
+                }
+                self.left_seats.remove(&player_idx);
+                return Ok(player_idx);
+            }
+        }
+
+        self.check_free_seat()?;
+        let player_metadata = match seed {
+            Some(random_seed) => PlayerMetadata {
+                random_seed,
+                address,
+            },
+            None => random_player_metadata(address),
+        };
+        Ok(self.push_player(player_metadata))
+    }
+
+    /// Take an anonymous seat with a random seed.
+    pub fn join_random(&mut self) -> Result<usize, &'static str> {
+        self.check_free_seat()?;
+        Ok(self.push_player(random_player_metadata([0; 20])))
+    }
+}
+
+impl<
+        PublicState: Serialize + Default + Send + Sync,
+        PrivateState: Default + Send + Sync,
+        GameAction: TurboActionSerialization + Send + Sync,
+    > TurboSession<PublicState, PrivateState, GameAction>
+{
     pub fn id(&self) -> String {
         self.id.clone()
     }
@@ -68,11 +240,82 @@ impl<
         &self.actions
     }
 
+    /// Number of actions in the log.
+    pub fn action_count(&self) -> usize {
+        self.action_ends.len()
+    }
+
     pub fn player_count(&self) -> usize {
         self.player_metadata.len()
     }
 
-    pub fn join(&mut self, player_metadata: PlayerMetadata) -> usize {
+    /// Persist the session to `store` now and after every change.
+    pub fn attach_store(&mut self, store: Arc<dyn SessionStore>) -> Result<(), &'static str> {
+        store.save(&self.record())?;
+        self.store = Some(store);
+        Ok(())
+    }
+
+    pub fn record(&self) -> SessionRecord {
+        SessionRecord {
+            id: self.id.clone(),
+            server_metadata: self.server_metadata.clone(),
+            player_metadata: self.player_metadata.clone(),
+            actions: self.actions.clone(),
+            is_revealed: self.is_revealed,
+        }
+    }
+
+    /// Stop persisting the session and notify subscribers, used when it is
+    /// closed or evicted.
+    pub fn close(&mut self) {
+        self.store = None;
+        let _ = self.events.send(SessionEvent::Closed);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<SessionEvent> {
+        self.events.subscribe()
+    }
+
+    pub fn created_at(&self) -> Instant {
+        self.created_at
+    }
+
+    pub fn last_active(&self) -> Instant {
+        self.last_active
+    }
+
+    /// Record activity on the session and save it to the attached store.
+    fn mark_changed(&mut self) {
+        self.last_active = Instant::now();
+
+        if let Some(store) = &self.store {
+            if let Err(e) = store.save(&self.record()) {
+                eprintln!("Failed to persist session {}: {}", self.id, e);
+            }
+        }
+    }
+
+    /// Commitment to the server seed, published before players join.
+    pub fn server_commitment(&self) -> [u8; 32] {
+        commit_seed(&self.server_metadata.random_seed)
+    }
+
+    /// Commitment to the seed combined from the server seed and the seed of
+    /// `player_idx`.
+    pub fn seed_commitment(&self, player_idx: usize) -> Option<[u8; 32]> {
+        let player_metadata = self.player_metadata.get(player_idx)?;
+        Some(commit_combined_seed(
+            &self.server_commitment(),
+            &player_metadata.random_seed,
+        ))
+    }
+
+    pub fn is_revealed(&self) -> bool {
+        self.is_revealed
+    }
+
+    fn push_player(&mut self, player_metadata: PlayerMetadata) -> usize {
         self.player_metadata.push(player_metadata);
 
         let player_idx = self.player_metadata.len() - 1;
@@ -84,22 +327,53 @@ impl<
         );
 
         self.contexts.push(context);
+        self.mark_changed();
 
         player_idx
     }
 
-    pub fn join_random(&mut self) -> usize {
-        let mut rng = thread_rng();
-        let player_random_seed = AffineG1::one() * Fr::random(&mut rng);
-        let player_metadata = PlayerMetadata {
-            random_seed: bn254_export_affine_g1_memcpy(&player_random_seed),
-        };
-        self.join(player_metadata)
+    /// Record that the player of `player_idx` left, returning whether every
+    /// seat has been left. Joining again takes the seat back.
+    pub fn leave(&mut self, player_idx: usize) -> bool {
+        self.left_seats.insert(player_idx);
+        self.left_seats.len() >= self.player_metadata.len()
+    }
+
+    /// Seat bound to `address`, if any.
+    pub fn seat_of(&self, address: &[u8; 20]) -> Option<usize> {
+        self.player_metadata
+            .iter()
+            .position(|metadata| &metadata.address == address)
     }
 
     pub fn dispatch(&mut self, action_raw: &[u8]) -> Result<(), &'static str> {
-        let (action, next_actions) = GameAction::deserialize(&action_raw[1..])?;
+        if self.is_revealed {
+            return Err("Server seed already revealed");
+        }
+
+        if self.is_bricked {
+            return Err("Session must be rolled back after a failed action");
+        }
+
+        let player_idx = self.apply(action_raw)?;
+        self.mark_changed();
+        let _ = self.events.send(SessionEvent::Dispatched { player_idx });
+
+        Ok(())
+    }
+
+    /// Run a single action through the reducer and append it to the log,
+    /// returning the player who played it.
+    fn apply(&mut self, action_raw: &[u8]) -> Result<usize, &'static str> {
+        if action_raw.len() < 2 {
+            return Err("Truncated action");
+        }
         let player_idx = action_raw[0] as usize;
+        if player_idx >= self.player_metadata.len() {
+            return Err("Invalid player index");
+        }
+
+        let (action, next_actions) = GameAction::deserialize(&action_raw[1..])?;
 
         if !next_actions.is_empty() {
             return Err("Dispatching multiple actions is not allowed");
@@ -116,19 +390,24 @@ impl<
                 &mut self.private_state,
                 &action,
                 &mut context,
-            );
-        }))
-        .map_err(|_| "Failed to dispatch action");
+            )
+        }));
 
-        if let Err(e) = result {
-            self.is_bricked = true;
-            return Err(e);
+        match result {
+            Ok(Ok(())) => {}
+            // Rejected actions leave the state untouched and are not logged
+            Ok(Err(e)) => return Err(e.into()),
+            Err(_) => {
+                self.is_bricked = true;
+                return Err("Failed to dispatch action");
+            }
         }
 
         self.actions.extend(action_raw);
+        self.action_ends.push(self.actions.len());
         self.contexts[player_idx] = context.inner;
 
-        Ok(())
+        Ok(player_idx)
     }
 
     pub fn sp1_stdin(&self) -> SP1Stdin {
@@ -146,11 +425,342 @@ impl<
     pub fn private_state(&self) -> &PrivateState {
         &self.private_state
     }
+}
 
+impl<
+        PublicState: Serialize + Default + PlayerView<PrivateState> + Send + Sync,
+        PrivateState: Default + Send + Sync,
+        GameAction: TurboActionSerialization + Send + Sync,
+    > TurboSession<PublicState, PrivateState, GameAction>
+{
+    /// State sent to `player_idx`: its view of the game and the response to
+    /// its last action.
     pub fn serialize_json(&self, player_idx: usize) -> Result<Value, &'static str> {
+        let context = self
+            .contexts
+            .get(player_idx)
+            .ok_or("Invalid player index")?;
+
         Ok(json!({
-            "public_state": self.public_state,
-            "client_response": self.contexts[player_idx].client_response,
+            "public_state": self.public_state.player_view(&self.private_state, player_idx),
+            "client_response": context.client_response,
         }))
     }
+
+    /// Public state as seen by spectators.
+    pub fn spectator_json(&self) -> Value {
+        self.public_state.spectator_view(&self.private_state)
+    }
+}
+
+impl<
+        PublicState: Serialize + Default + HasTerminalState + Send + Sync,
+        PrivateState: Default + Send + Sync,
+        GameAction: TurboActionSerialization + Send + Sync,
+    > TurboSession<PublicState, PrivateState, GameAction>
+{
+    /// Reveal the server seed along with every draw made so far, once the
+    /// game is over.
+    ///
+    /// No action is accepted afterwards, since anyone could predict the next draws.
+    pub fn reveal(&mut self) -> Result<FairnessReveal, &'static str> {
+        if !self.public_state.is_terminal() {
+            return Err("Game is not over");
+        }
+
+        self.is_revealed = true;
+        self.mark_changed();
+
+        Ok(FairnessReveal {
+            server_commitment: self.server_commitment(),
+            server_seed: self.server_metadata.random_seed,
+            player_seeds: self
+                .player_metadata
+                .iter()
+                .map(|metadata| metadata.random_seed)
+                .collect(),
+            draws: self
+                .contexts
+                .iter()
+                .map(|context| context.draws().to_vec())
+                .collect(),
+        })
+    }
+}
+
+impl<
+        PublicState: Serialize + Default + TurboInitState + Clone + Send + Sync,
+        PrivateState: Default + Clone + Send + Sync,
+        GameAction: TurboActionSerialization + Send + Sync,
+    > TurboSession<PublicState, PrivateState, GameAction>
+{
+    /// Snapshot the current state so later rollbacks replay from here,
+    /// returning the number of actions it covers.
+    pub fn checkpoint(&mut self) -> Result<usize, &'static str> {
+        if self.is_bricked {
+            return Err("Session must be rolled back after a failed action");
+        }
+
+        let action_count = self.action_count();
+        self.snapshots
+            .retain(|snapshot| snapshot.action_count != action_count);
+        self.snapshots.push(SessionSnapshot {
+            action_count,
+            public_state: self.public_state.clone(),
+            private_state: self.private_state.clone(),
+            contexts: self.contexts.clone(),
+        });
+
+        Ok(action_count)
+    }
+
+    /// Drop every action after the first `action_count` ones. The state is
+    /// rebuilt from the closest snapshot by replaying the log, so it always
+    /// matches what `sp1_stdin` proves. Also recovers a session bricked by a
+    /// panicking reducer.
+    pub fn rollback(&mut self, action_count: usize) -> Result<(), &'static str> {
+        // The draws after a reveal are public, replaying them would let
+        // players pick their outcomes
+        if self.is_revealed {
+            return Err("Server seed already revealed");
+        }
+
+        self.rewind(action_count)?;
+        self.mark_changed();
+        let _ = self.events.send(SessionEvent::RolledBack { action_count });
+
+        Ok(())
+    }
+
+    fn rewind(&mut self, action_count: usize) -> Result<(), &'static str> {
+        if action_count > self.action_count() {
+            return Err("Rollback past the end of the log");
+        }
+
+        let actions = std::mem::take(&mut self.actions);
+        let action_ends = std::mem::take(&mut self.action_ends);
+
+        self.snapshots
+            .retain(|snapshot| snapshot.action_count <= action_count);
+        let base = match self.snapshots.last() {
+            Some(snapshot) => {
+                self.public_state = snapshot.public_state.clone();
+                self.private_state = snapshot.private_state.clone();
+                self.contexts = snapshot.contexts.clone();
+                snapshot.action_count
+            }
+            None => {
+                self.public_state = PublicState::init_state(&self.server_metadata.game_config)?;
+                self.private_state = PrivateState::default();
+                self.contexts.clear();
+                0
+            }
+        };
+
+        // Seats taken after the snapshot start from a fresh context
+        for player_idx in self.contexts.len()..self.player_metadata.len() {
+            self.contexts.push(TurboActionContextInner::new(
+                &self.server_metadata,
+                &self.player_metadata[player_idx],
+                player_idx,
+            ));
+        }
+
+        let base_end = if base == 0 { 0 } else { action_ends[base - 1] };
+        self.actions = actions[..base_end].to_vec();
+        self.action_ends = action_ends[..base].to_vec();
+        self.is_bricked = false;
+
+        for i in base..action_count {
+            let start = if i == 0 { 0 } else { action_ends[i - 1] };
+            self.apply(&actions[start..action_ends[i]])?;
+        }
+
+        Ok(())
+    }
+
+    /// New session replaying the first `action_count` actions, to explore
+    /// other lines of play. Only revealed sessions can be forked, since the
+    /// fork shares their seeds.
+    pub fn fork(&self, action_count: usize) -> Result<Self, &'static str> {
+        if !self.is_revealed {
+            return Err("Only revealed sessions can be forked");
+        }
+        if action_count > self.action_count() {
+            return Err("Fork past the end of the log");
+        }
+
+        let mut fork =
+            Self::new_with_config(self.reducer, self.server_metadata.game_config.clone())?;
+        fork.server_metadata = ServerMetadata {
+            // A fresh nonce keeps proofs of the fork apart from the original match
+            match_nonce: thread_rng().gen(),
+            ..self.server_metadata.clone()
+        };
+        for player_metadata in &self.player_metadata {
+            fork.push_player(player_metadata.clone());
+        }
+
+        let end = if action_count == 0 {
+            0
+        } else {
+            self.action_ends[action_count - 1]
+        };
+        let mut remaining = &self.actions[..end];
+        while !remaining.is_empty() {
+            let (_, next_actions) = GameAction::deserialize(&remaining[1..])?;
+            let action_len = remaining.len() - next_actions.len();
+
+            fork.apply(&remaining[..action_len])?;
+            remaining = next_actions;
+        }
+
+        Ok(fork)
+    }
+}
+
+impl<
+        PublicState: Serialize + Default + TurboInitState + UndoRules + Clone + Send + Sync,
+        PrivateState: Default + Clone + Send + Sync,
+        GameAction: TurboActionSerialization + Send + Sync,
+    > TurboSession<PublicState, PrivateState, GameAction>
+{
+    /// Take back the last action, if `player_idx` played it and the game
+    /// rules allow it.
+    pub fn undo(&mut self, player_idx: usize) -> Result<(), &'static str> {
+        let action_count = self.action_count();
+        if action_count == 0 {
+            return Err("No action to undo");
+        }
+
+        let start = if action_count == 1 {
+            0
+        } else {
+            self.action_ends[action_count - 2]
+        };
+        if self.actions[start] as usize != player_idx {
+            return Err("Last action was played by another player");
+        }
+        if !self.public_state.can_undo(player_idx) {
+            return Err("Undo is not allowed");
+        }
+
+        self.rollback(action_count - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game_lib::{
+        action::GameAction,
+        error::GameError,
+        reducer::reducer,
+        state::{GamePrivateState, GamePublicState},
+    };
+
+    type Session = TurboSession<GamePublicState, GamePrivateState, GameAction>;
+
+    fn new_session(
+        reducer: TurboReducer<GamePublicState, GamePrivateState, GameAction>,
+    ) -> Session {
+        let mut session = Session::new(reducer);
+        session.join_random().unwrap();
+        session.join_random().unwrap();
+        session
+    }
+
+    fn play(session: &mut Session, columns: &[u8]) {
+        for &column in columns {
+            let player_idx = session.public_state().current_player - 1;
+            session.dispatch(&[player_idx, column]).unwrap();
+        }
+    }
+
+    fn panicking_reducer(
+        public_state: &mut GamePublicState,
+        private_state: &mut GamePrivateState,
+        action: &GameAction,
+        context: &mut TurboActionContext,
+    ) -> Result<(), GameError> {
+        if let GameAction::DropPiece(9) = action {
+            panic!("Reducer bug");
+        }
+        reducer(public_state, private_state, action, context)
+    }
+
+    #[test]
+    fn rollback_matches_replay() {
+        let mut session = new_session(reducer);
+        play(&mut session, &[3]);
+        session.checkpoint().unwrap();
+        play(&mut session, &[3, 4, 2, 5]);
+
+        let mut record = session.record();
+        record.actions.truncate(session.action_ends[2]);
+        session.rollback(3).unwrap();
+        let replayed = Session::restore(reducer, record).unwrap();
+
+        assert_eq!(session.action_count(), 3);
+        assert_eq!(session.sp1_stdin().buffer, replayed.sp1_stdin().buffer);
+        assert_eq!(
+            serde_json::to_value(session.public_state()).unwrap(),
+            serde_json::to_value(replayed.public_state()).unwrap()
+        );
+    }
+
+    #[test]
+    fn rollback_recovers_bricked_session() {
+        let mut session = new_session(panicking_reducer);
+        play(&mut session, &[3]);
+
+        assert!(session.dispatch(&[1, 9]).is_err());
+        assert!(session.is_bricked);
+        assert!(session.dispatch(&[1, 4]).is_err());
+
+        session.rollback(session.action_count()).unwrap();
+        assert!(!session.is_bricked);
+        play(&mut session, &[4]);
+        assert_eq!(session.action_count(), 2);
+    }
+
+    #[test]
+    fn undo_only_takes_back_own_move() {
+        let mut session = new_session(reducer);
+        play(&mut session, &[3]);
+
+        assert_eq!(
+            session.undo(1),
+            Err("Last action was played by another player")
+        );
+        session.undo(0).unwrap();
+        assert_eq!(session.action_count(), 0);
+    }
+
+    #[test]
+    fn fork_needs_revealed_session() {
+        let mut session = new_session(reducer);
+        play(&mut session, &[0, 1, 0, 1, 0, 1]);
+
+        assert!(session.reveal().is_err());
+        assert_eq!(
+            session.fork(2).err(),
+            Some("Only revealed sessions can be forked")
+        );
+
+        play(&mut session, &[0]);
+        session.reveal().unwrap();
+        let fork = session.fork(2).unwrap();
+        assert_eq!(fork.action_count(), 2);
+        assert_eq!(fork.actions()[..], session.actions()[..4]);
+    }
+
+    #[test]
+    fn restore_rejects_malformed_actions() {
+        for actions in [vec![0x05], vec![0x00], vec![0x05, 0x03]] {
+            let mut record = new_session(reducer).record();
+            record.actions = actions;
+            assert!(Session::restore(reducer, record).is_err());
+        }
+    }
 }
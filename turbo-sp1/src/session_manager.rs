@@ -1,12 +1,33 @@
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 
 use turbo_program::program::TurboReducer;
-use turbo_program::traits::TurboActionSerialization;
+use turbo_program::traits::{TurboActionSerialization, TurboInitState};
 
 use crate::session::TurboSession;
+use crate::session_store::{MemorySessionStore, SessionRecord, SessionStore};
+
+/// When idle or old sessions get evicted. `None` disables a limit.
+#[derive(Debug, Clone, Default)]
+pub struct SessionLimits {
+    pub idle_timeout: Option<Duration>,
+    pub max_age: Option<Duration>,
+}
+
+impl SessionLimits {
+    fn is_expired(&self, created_at: Instant, last_active: Instant, now: Instant) -> bool {
+        let idle = self
+            .idle_timeout
+            .is_some_and(|timeout| now.duration_since(last_active) > timeout);
+        let old = self
+            .max_age
+            .is_some_and(|max_age| now.duration_since(created_at) > max_age);
+        idle || old
+    }
+}
 
 pub struct SessionManager<PublicState, PrivateState, GameAction>
 where
@@ -16,6 +37,8 @@ where
 {
     sessions:
         Mutex<HashMap<String, Arc<Mutex<TurboSession<PublicState, PrivateState, GameAction>>>>>,
+    store: Arc<dyn SessionStore>,
+    limits: SessionLimits,
 }
 
 impl<
@@ -36,18 +59,33 @@ impl<
     > SessionManager<PublicState, PrivateState, GameAction>
 {
     pub fn new() -> Self {
+        Self::with_store(Arc::new(MemorySessionStore::new()))
+    }
+
+    /// Create a manager persisting every session to `store`.
+    pub fn with_store(store: Arc<dyn SessionStore>) -> Self {
         Self {
             sessions: Mutex::new(HashMap::new()),
+            store,
+            limits: SessionLimits::default(),
         }
     }
 
-    pub async fn create_session(
+    pub fn with_limits(mut self, limits: SessionLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    async fn insert_session(
         &mut self,
-        reducer: TurboReducer<PublicState, PrivateState, GameAction>,
+        mut session: TurboSession<PublicState, PrivateState, GameAction>,
     ) -> String {
-        let session = TurboSession::new(reducer);
         let id = session.id();
 
+        if let Err(e) = session.attach_store(self.store.clone()) {
+            eprintln!("Failed to persist session {}: {}", id, e);
+        }
+
         let mut sessions = self.sessions.lock().await;
         sessions.insert(session.id(), Arc::new(Mutex::new(session)));
         id
@@ -60,4 +98,124 @@ impl<
         let sessions = self.sessions.lock().await;
         sessions.get(id).cloned()
     }
+
+    /// Record of session `id`, live or only saved in the store.
+    pub async fn load_record(&self, id: &str) -> Result<Option<SessionRecord>, &'static str> {
+        match self.get_session(id).await {
+            Some(session) => Ok(Some(session.lock().await.record())),
+            None => self.store.load(id),
+        }
+    }
+
+    /// Remove a session and its stored record, returning whether it was live.
+    pub async fn close_session(&self, id: &str) -> Result<bool, &'static str> {
+        let session = self.sessions.lock().await.remove(id);
+        let found = match session {
+            Some(session) => {
+                session.lock().await.close();
+                true
+            }
+            None => false,
+        };
+
+        self.store.remove(id)?;
+        Ok(found)
+    }
+
+    /// Evict the sessions past their idle timeout or max age, except the
+    /// `pinned` ones and the ones currently in use. Returns the evicted ids.
+    pub async fn evict_expired(&self, pinned: &HashSet<String>) -> Vec<String> {
+        let mut sessions = self.sessions.lock().await;
+        let now = Instant::now();
+
+        let expired: Vec<String> = sessions
+            .iter()
+            .filter(|(id, _)| !pinned.contains(*id))
+            .filter_map(|(id, session)| {
+                let session = session.try_lock().ok()?;
+                self.limits
+                    .is_expired(session.created_at(), session.last_active(), now)
+                    .then(|| id.clone())
+            })
+            .collect();
+
+        for id in &expired {
+            if let Some(session) = sessions.remove(id) {
+                session.lock().await.close();
+            }
+            if let Err(e) = self.store.remove(id) {
+                eprintln!("Failed to remove session {}: {}", id, e);
+            }
+        }
+
+        expired
+    }
+}
+
+impl<
+        PublicState: Serialize + Default + TurboInitState + Send + Sync,
+        PrivateState: Default + Send + Sync,
+        GameAction: TurboActionSerialization + Send + Sync,
+    > SessionManager<PublicState, PrivateState, GameAction>
+{
+    pub async fn create_session(
+        &mut self,
+        reducer: TurboReducer<PublicState, PrivateState, GameAction>,
+    ) -> String {
+        self.insert_session(TurboSession::new(reducer)).await
+    }
+
+    pub async fn create_session_with_config(
+        &mut self,
+        reducer: TurboReducer<PublicState, PrivateState, GameAction>,
+        game_config: Vec<u8>,
+    ) -> Result<String, &'static str> {
+        let session = TurboSession::new_with_config(reducer, game_config)?;
+        Ok(self.insert_session(session).await)
+    }
+
+    /// Rebuild every session saved in the store, returning how many were restored.
+    ///
+    /// Records whose actions no longer replay cleanly are skipped.
+    pub fn restore_sessions(
+        &mut self,
+        reducer: TurboReducer<PublicState, PrivateState, GameAction>,
+    ) -> Result<usize, &'static str> {
+        let records = self.store.load_all()?;
+        let sessions = self.sessions.get_mut();
+
+        for record in records {
+            let id = record.id.clone();
+            let mut session = match TurboSession::restore(reducer, record) {
+                Ok(session) => session,
+                Err(e) => {
+                    eprintln!("Failed to restore session {}: {}", id, e);
+                    continue;
+                }
+            };
+            session.attach_store(self.store.clone())?;
+            sessions.insert(id, Arc::new(Mutex::new(session)));
+        }
+
+        Ok(sessions.len())
+    }
+}
+
+impl<
+        PublicState: Serialize + Default + TurboInitState + Clone + Send + Sync,
+        PrivateState: Default + Clone + Send + Sync,
+        GameAction: TurboActionSerialization + Send + Sync,
+    > SessionManager<PublicState, PrivateState, GameAction>
+{
+    /// Fork session `id` after its first `action_count` actions into a new
+    /// session, returning the id of the fork.
+    pub async fn fork_session(
+        &mut self,
+        id: &str,
+        action_count: usize,
+    ) -> Result<String, &'static str> {
+        let session = self.get_session(id).await.ok_or("Session not found")?;
+        let fork = session.lock().await.fork(action_count)?;
+        Ok(self.insert_session(fork).await)
+    }
 }
@@ -2,21 +2,45 @@ use std::sync::Arc;
 
 use serde::Serialize;
 use tokio::sync::Mutex;
-use turbo_program::{program::TurboReducer, traits::TurboActionSerialization};
+use turbo_program::{
+    program::TurboReducer,
+    traits::{TurboActionSerialization, TurboInitState},
+};
 
 use crate::{session::TurboSession, session_manager::SessionManager};
 
+/// Seats a batch of actions may act for.
+#[derive(Debug, Clone, Copy)]
+pub enum Seats {
+    /// Any seat, creating missing ones. Used for sessions built in one go
+    /// from a full action list.
+    Open,
+    /// Only the seat owned by the caller.
+    Owned(usize),
+}
+
+impl Seats {
+    /// Seat used for JSON actions, which are not tagged with a player.
+    fn default_seat(self) -> usize {
+        match self {
+            Seats::Open => 0,
+            Seats::Owned(player_idx) => player_idx,
+        }
+    }
+}
+
 pub async fn dispatch_actions<PublicState, PrivateState, GameAction>(
     session: Arc<Mutex<TurboSession<PublicState, PrivateState, GameAction>>>,
     actions: serde_json::Value,
-    player_idx: usize,
+    seats: Seats,
 ) -> Result<(), &'static str>
 where
-    PublicState: Serialize + Default + Send + Sync,
+    PublicState: Serialize + Default + TurboInitState + Send + Sync,
     PrivateState: Default + Send + Sync,
     GameAction: TurboActionSerialization + Send + Sync,
 {
     let mut session_guard = session.lock().await;
+    let player_idx = seats.default_seat();
 
     let remaining_actions_vec = match actions {
         serde_json::Value::Array(_) => {
@@ -42,18 +66,36 @@ where
     let mut remaining_actions = &remaining_actions_vec[..];
 
     while !remaining_actions.is_empty() {
+        if remaining_actions.len() < 2 {
+            Err("Truncated action")?;
+        }
         let player_idx = remaining_actions[0] as usize;
 
-        if player_idx > 100 {
-            Err("Max 100 players")?;
-        }
+        match seats {
+            Seats::Open => {
+                if player_idx > 100 {
+                    Err("Max 100 players")?;
+                }
+                if session_guard
+                    .public_state()
+                    .max_players()
+                    .is_some_and(|max_players| player_idx >= max_players)
+                {
+                    Err("Invalid player index")?;
+                }
 
-        while player_idx >= session_guard.player_count() {
-            session_guard.join_random();
+                while player_idx >= session_guard.player_count() {
+                    session_guard.join_random()?;
+                }
+            }
+            Seats::Owned(owned_idx) => {
+                if player_idx != owned_idx {
+                    Err("Action for a seat the player does not own")?;
+                }
+            }
         }
 
-        let (_action, next_actions) =
-            GameAction::deserialize(&remaining_actions[1..]).expect("Failed to deserialize action");
+        let (_action, next_actions) = GameAction::deserialize(&remaining_actions[1..])?;
 
         let action_bytes = &remaining_actions[0..remaining_actions.len() - next_actions.len()];
         session_guard.dispatch(action_bytes)?;
@@ -70,7 +112,7 @@ pub async fn create_session_json<PublicState, PrivateState, GameAction>(
     actions: serde_json::Value,
 ) -> Result<String, &'static str>
 where
-    PublicState: Serialize + Default + Send + Sync,
+    PublicState: Serialize + Default + TurboInitState + Send + Sync,
     PrivateState: Default + Send + Sync,
     GameAction: TurboActionSerialization + Send + Sync,
 {
@@ -80,7 +122,7 @@ where
         .await
         .ok_or("Failed to create session")?;
 
-    dispatch_actions(session, actions, 0).await?;
+    dispatch_actions(session, actions, Seats::Open).await?;
 
     Ok(session_id)
 }
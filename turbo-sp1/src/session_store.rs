@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex as StdMutex;
+
+use serde::{Deserialize, Serialize};
+use turbo_program::metadata::{PlayerMetadata, ServerMetadata};
+
+/// Everything needed to rebuild a session: the metadata fed to the program and
+/// the player tagged action log, replayed through the reducer on restore.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub id: String,
+    pub server_metadata: ServerMetadata,
+    pub player_metadata: Vec<PlayerMetadata>,
+    pub actions: Vec<u8>,
+    pub is_revealed: bool,
+}
+
+pub trait SessionStore: Send + Sync {
+    fn save(&self, record: &SessionRecord) -> Result<(), &'static str>;
+    fn load(&self, id: &str) -> Result<Option<SessionRecord>, &'static str>;
+    fn load_all(&self) -> Result<Vec<SessionRecord>, &'static str>;
+    fn remove(&self, id: &str) -> Result<(), &'static str>;
+}
+
+/// Keeps records in memory, so sessions do not outlive the process.
+#[derive(Default)]
+pub struct MemorySessionStore {
+    records: StdMutex<HashMap<String, SessionRecord>>,
+}
+
+impl MemorySessionStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl SessionStore for MemorySessionStore {
+    fn save(&self, record: &SessionRecord) -> Result<(), &'static str> {
+        let mut records = self.records.lock().map_err(|_| "Failed to lock store")?;
+        records.insert(record.id.clone(), record.clone());
+        Ok(())
+    }
+
+    fn load(&self, id: &str) -> Result<Option<SessionRecord>, &'static str> {
+        let records = self.records.lock().map_err(|_| "Failed to lock store")?;
+        Ok(records.get(id).cloned())
+    }
+
+    fn load_all(&self) -> Result<Vec<SessionRecord>, &'static str> {
+        let records = self.records.lock().map_err(|_| "Failed to lock store")?;
+        Ok(records.values().cloned().collect())
+    }
+
+    fn remove(&self, id: &str) -> Result<(), &'static str> {
+        let mut records = self.records.lock().map_err(|_| "Failed to lock store")?;
+        records.remove(id);
+        Ok(())
+    }
+}
+
+/// Stores each record as `<dir>/<id>.json`.
+///
+/// Records are written to a temporary file first and renamed into place, so a
+/// crash mid-write leaves the previous record intact.
+pub struct FileSessionStore {
+    dir: PathBuf,
+}
+
+impl FileSessionStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self, &'static str> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir).map_err(|_| "Failed to create session directory")?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, id: &str) -> Result<PathBuf, &'static str> {
+        // Session ids are UUIDs, anything else could escape the directory
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err("Invalid session id");
+        }
+        Ok(self.dir.join(format!("{}.json", id)))
+    }
+}
+
+impl SessionStore for FileSessionStore {
+    fn save(&self, record: &SessionRecord) -> Result<(), &'static str> {
+        let path = self.path(&record.id)?;
+        let tmp_path = path.with_extension("json.tmp");
+
+        let data = serde_json::to_vec(record).map_err(|_| "Failed to serialize session")?;
+        fs::write(&tmp_path, data).map_err(|_| "Failed to write session")?;
+        fs::rename(&tmp_path, &path).map_err(|_| "Failed to write session")
+    }
+
+    fn load(&self, id: &str) -> Result<Option<SessionRecord>, &'static str> {
+        let path = self.path(id)?;
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let data = fs::read(&path).map_err(|_| "Failed to read session")?;
+        serde_json::from_slice(&data)
+            .map(Some)
+            .map_err(|_| "Failed to deserialize session")
+    }
+
+    /// Every readable record. Unreadable or corrupt files are logged and
+    /// skipped, so they don't keep the others from being restored.
+    fn load_all(&self) -> Result<Vec<SessionRecord>, &'static str> {
+        let entries = fs::read_dir(&self.dir).map_err(|_| "Failed to read session directory")?;
+
+        let mut records = Vec::new();
+        for entry in entries {
+            let path = entry
+                .map_err(|_| "Failed to read session directory")?
+                .path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+
+            let record = fs::read(&path)
+                .map_err(|_| "Failed to read session")
+                .and_then(|data| {
+                    serde_json::from_slice(&data).map_err(|_| "Failed to deserialize session")
+                });
+            match record {
+                Ok(record) => records.push(record),
+                Err(e) => eprintln!("Skipping session file {}: {}", path.display(), e),
+            }
+        }
+        Ok(records)
+    }
+
+    fn remove(&self, id: &str) -> Result<(), &'static str> {
+        let path = self.path(id)?;
+        if path.exists() {
+            fs::remove_file(&path).map_err(|_| "Failed to remove session")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_store_roundtrip() {
+        let dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let store = FileSessionStore::new(&dir).unwrap();
+
+        let record = SessionRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            server_metadata: ServerMetadata {
+                random_seed: [1; 16],
+                match_nonce: [2; 32],
+                game_config: vec![6, 7, 4],
+            },
+            player_metadata: vec![PlayerMetadata {
+                random_seed: [3; 16],
+                address: [4; 20],
+            }],
+            actions: vec![0, 3, 1, 4],
+            is_revealed: false,
+        };
+        store.save(&record).unwrap();
+        fs::write(dir.join("corrupt.json"), b"{").unwrap();
+
+        let loaded = store.load(&record.id).unwrap().unwrap();
+        assert_eq!(loaded.actions, record.actions);
+        assert_eq!(store.load_all().unwrap().len(), 1);
+        assert!(store.load("../escape").is_err());
+
+        store.remove(&record.id).unwrap();
+        assert!(store.load(&record.id).unwrap().is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
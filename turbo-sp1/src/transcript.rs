@@ -0,0 +1,166 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use sp1_sdk::{SP1ProofWithPublicValues, SP1Stdin, SP1VerifyingKey};
+use turbo_program::metadata::{PlayerMetadata, ServerMetadata};
+
+use crate::session_store::SessionRecord;
+
+/// Version written to new transcripts. Readers reject other versions.
+pub const TRANSCRIPT_VERSION: u32 = 1;
+
+/// Prefix of the binary form, followed by the bincode encoded transcript.
+const BINARY_MAGIC: &[u8; 4] = b"TBTX";
+
+/// Proof of a transcript along with the key to verify it.
+#[derive(Serialize, Deserialize)]
+pub struct TranscriptProof {
+    pub vk: SP1VerifyingKey,
+    pub proof: SP1ProofWithPublicValues,
+}
+
+/// Everything needed to re-execute a match and check its proof offline: the
+/// inputs of the game program, the final state and optionally the proof.
+#[derive(Serialize, Deserialize)]
+pub struct Transcript {
+    pub version: u32,
+    pub server_metadata: ServerMetadata,
+    pub player_metadata: Vec<PlayerMetadata>,
+    /// Player tagged action stream, as read by `turbo_program`.
+    pub actions: Vec<u8>,
+    /// ABI encoded final public state, empty if the match was not run yet.
+    pub public_state: Vec<u8>,
+    pub proof: Option<TranscriptProof>,
+}
+
+impl Transcript {
+    pub fn new(
+        server_metadata: ServerMetadata,
+        player_metadata: Vec<PlayerMetadata>,
+        actions: Vec<u8>,
+    ) -> Self {
+        Self {
+            version: TRANSCRIPT_VERSION,
+            server_metadata,
+            player_metadata,
+            actions,
+            public_state: Vec::new(),
+            proof: None,
+        }
+    }
+
+    pub fn from_record(record: &SessionRecord) -> Self {
+        Self::new(
+            record.server_metadata.clone(),
+            record.player_metadata.clone(),
+            record.actions.clone(),
+        )
+    }
+
+    /// Read the inputs back from the stdin built by `TurboSession::sp1_stdin`.
+    pub fn from_stdin(stdin: &SP1Stdin) -> Result<Self, &'static str> {
+        let [server_metadata, player_metadata, actions] = &stdin.buffer[..] else {
+            return Err("Unexpected stdin layout");
+        };
+
+        Ok(Self::new(
+            bincode::deserialize(server_metadata).map_err(|_| "Invalid server metadata")?,
+            bincode::deserialize(player_metadata).map_err(|_| "Invalid player metadata")?,
+            bincode::deserialize(actions).map_err(|_| "Invalid actions")?,
+        ))
+    }
+
+    /// Stdin of the game program for this match.
+    pub fn sp1_stdin(&self) -> SP1Stdin {
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&self.server_metadata);
+        stdin.write(&self.player_metadata);
+        stdin.write(&self.actions);
+        stdin
+    }
+
+    /// Session record of the match, e.g. to replay it.
+    pub fn to_record(&self, id: String) -> SessionRecord {
+        SessionRecord {
+            id,
+            server_metadata: self.server_metadata.clone(),
+            player_metadata: self.player_metadata.clone(),
+            actions: self.actions.clone(),
+            is_revealed: true,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<Vec<u8>, &'static str> {
+        serde_json::to_vec_pretty(self).map_err(|_| "Failed to serialize transcript")
+    }
+
+    pub fn to_binary(&self) -> Result<Vec<u8>, &'static str> {
+        let mut data = BINARY_MAGIC.to_vec();
+        bincode::serialize_into(&mut data, self).map_err(|_| "Failed to serialize transcript")?;
+        Ok(data)
+    }
+
+    /// Parse either form, telling them apart by the binary prefix.
+    pub fn decode(data: &[u8]) -> Result<Self, &'static str> {
+        let transcript: Self = match data.strip_prefix(BINARY_MAGIC) {
+            Some(binary) => {
+                bincode::deserialize(binary).map_err(|_| "Failed to deserialize transcript")?
+            }
+            None => serde_json::from_slice(data).map_err(|_| "Failed to deserialize transcript")?,
+        };
+
+        if transcript.version != TRANSCRIPT_VERSION {
+            return Err("Unsupported transcript version");
+        }
+        Ok(transcript)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, &'static str> {
+        Self::decode(&fs::read(path).map_err(|_| "Failed to read transcript")?)
+    }
+
+    /// Write the binary form, or JSON if `path` ends with `.json`.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), &'static str> {
+        let path = path.as_ref();
+        let data = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => self.to_json()?,
+            _ => self.to_binary()?,
+        };
+        fs::write(path, data).map_err(|_| "Failed to write transcript")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transcript_roundtrip() {
+        let transcript = Transcript::new(
+            ServerMetadata {
+                random_seed: [1; 16],
+                match_nonce: [2; 32],
+                game_config: vec![6, 7, 4],
+            },
+            vec![PlayerMetadata {
+                random_seed: [3; 16],
+                address: [4; 20],
+            }],
+            vec![0, 3, 1, 4],
+        );
+
+        for data in [
+            transcript.to_json().unwrap(),
+            transcript.to_binary().unwrap(),
+        ] {
+            let decoded = Transcript::decode(&data).unwrap();
+            assert_eq!(decoded.actions, transcript.actions);
+            assert_eq!(decoded.player_metadata[0].address, [4; 20]);
+        }
+
+        let stdin = transcript.sp1_stdin();
+        let from_stdin = Transcript::from_stdin(&stdin).unwrap();
+        assert_eq!(from_stdin.server_metadata.match_nonce, [2; 32]);
+    }
+}
@@ -32,6 +32,14 @@ impl ServerError {
         warp::reject::custom(Self::new(message, 404))
     }
 
+    pub fn unauthorized(message: String) -> Rejection {
+        warp::reject::custom(Self::new(message, 401))
+    }
+
+    pub fn forbidden(message: String) -> Rejection {
+        warp::reject::custom(Self::new(message, 403))
+    }
+
     pub fn message(&self) -> String {
         self.message.clone()
     }
@@ -0,0 +1,577 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use futures_util::{SinkExt, StreamExt};
+use serde::Serialize;
+use serde_json::json;
+use tokio::sync::{broadcast, mpsc, Mutex};
+use turbo_program::{
+    program::TurboReducer,
+    traits::{HasTerminalState, PlayerView, TurboActionSerialization, TurboInitState, UndoRules},
+};
+use turbo_protocol::{
+    ClientEnvelope, ClientMessage, ErrorCode, ProtocolError, ServerEnvelope, ServerMessage,
+    PROTOCOL_VERSION,
+};
+use warp::ws::{Message, WebSocket};
+
+use crate::auth::{new_challenge, verify_challenge};
+use crate::backend::BACKEND_NAMES;
+use crate::proof_worker::{submit_proof_job, ProofJob, ProofRequest};
+use crate::prove_queue::{ProofUpdate, ProveQueue};
+use crate::session::{decode_seed, SessionEvent, TurboSession};
+use crate::session_manager::SessionManager;
+use crate::session_simple::{dispatch_actions, Seats};
+
+/// Shared server state used by every WebSocket connection.
+pub struct WsContext<PublicState, PrivateState, GameAction>
+where
+    PublicState: Serialize + Default + Send + Sync,
+    PrivateState: Default + Send + Sync,
+    GameAction: TurboActionSerialization + Send + Sync,
+{
+    pub reducer: TurboReducer<PublicState, PrivateState, GameAction>,
+    pub session_manager: Arc<Mutex<SessionManager<PublicState, PrivateState, GameAction>>>,
+    pub prove_queue: Arc<ProveQueue>,
+    pub tx_jobs: Arc<mpsc::UnboundedSender<ProofJob>>,
+}
+
+impl<PublicState, PrivateState, GameAction> Clone
+    for WsContext<PublicState, PrivateState, GameAction>
+where
+    PublicState: Serialize + Default + Send + Sync,
+    PrivateState: Default + Send + Sync,
+    GameAction: TurboActionSerialization + Send + Sync,
+{
+    fn clone(&self) -> Self {
+        Self {
+            reducer: self.reducer,
+            session_manager: self.session_manager.clone(),
+            prove_queue: self.prove_queue.clone(),
+            tx_jobs: self.tx_jobs.clone(),
+        }
+    }
+}
+
+type SharedSession<PublicState, PrivateState, GameAction> =
+    Arc<Mutex<TurboSession<PublicState, PrivateState, GameAction>>>;
+
+/// Session joined by a connection.
+struct ActiveSession<PublicState, PrivateState, GameAction>
+where
+    PublicState: Serialize + Default + Send + Sync,
+    PrivateState: Default + Send + Sync,
+    GameAction: TurboActionSerialization + Send + Sync,
+{
+    id: String,
+    session: SharedSession<PublicState, PrivateState, GameAction>,
+    /// `None` for spectators.
+    seat: Option<usize>,
+    events: broadcast::Receiver<SessionEvent>,
+}
+
+/// State of a single connection.
+struct Connection<PublicState, PrivateState, GameAction>
+where
+    PublicState: Serialize + Default + Send + Sync,
+    PrivateState: Default + Send + Sync,
+    GameAction: TurboActionSerialization + Send + Sync,
+{
+    ctx: WsContext<PublicState, PrivateState, GameAction>,
+    handshake_done: bool,
+    challenge: String,
+    /// Address proven by `authenticate`.
+    address: Option<[u8; 20]>,
+    active: Option<ActiveSession<PublicState, PrivateState, GameAction>>,
+    proof_id: Option<String>,
+    /// Every proof requested on this connection, whose updates are pushed.
+    proof_ids: HashSet<String>,
+}
+
+fn no_active_session() -> ProtocolError {
+    ProtocolError::new(ErrorCode::NoActiveSession, "No active session")
+}
+
+impl<PublicState, PrivateState, GameAction> Connection<PublicState, PrivateState, GameAction>
+where
+    PublicState: Serialize
+        + Default
+        + TurboInitState
+        + PlayerView<PrivateState>
+        + UndoRules
+        + HasTerminalState
+        + Clone
+        + Send
+        + Sync,
+    PrivateState: Default + Serialize + Clone + Send + Sync,
+    GameAction: TurboActionSerialization + Send + Sync,
+{
+    fn new(ctx: WsContext<PublicState, PrivateState, GameAction>) -> Self {
+        Self {
+            ctx,
+            handshake_done: false,
+            challenge: new_challenge(),
+            address: None,
+            active: None,
+            proof_id: None,
+            proof_ids: HashSet::new(),
+        }
+    }
+
+    /// Joined session and seat, failing for spectators.
+    fn active_session(
+        &self,
+    ) -> Result<(SharedSession<PublicState, PrivateState, GameAction>, usize), ProtocolError> {
+        let active = self.active.as_ref().ok_or_else(no_active_session)?;
+        let player_idx = active.seat.ok_or_else(|| {
+            ProtocolError::new(ErrorCode::ReadOnly, "Spectators can't act on the session")
+        })?;
+        Ok((active.session.clone(), player_idx))
+    }
+
+    /// Public state and client response of the session as seen by this
+    /// player, or by spectators.
+    async fn state_message(&self) -> Result<ServerMessage, ProtocolError> {
+        let active = self.active.as_ref().ok_or_else(no_active_session)?;
+        let session = active.session.lock().await;
+        let mut state = match active.seat {
+            Some(player_idx) => session
+                .serialize_json(player_idx)
+                .map_err(|e| ProtocolError::new(ErrorCode::Internal, e))?,
+            None => json!({
+                "public_state": session.spectator_json(),
+                "client_response": null,
+            }),
+        };
+
+        Ok(ServerMessage::State {
+            public_state: state["public_state"].take(),
+            client_response: state["client_response"].take(),
+        })
+    }
+
+    /// Message to push for a session event, if any.
+    async fn on_session_event(
+        &mut self,
+        event: Result<SessionEvent, broadcast::error::RecvError>,
+    ) -> Option<ServerMessage> {
+        let active = self.active.as_ref()?;
+        match event {
+            // The player already got the state in the reply to its action
+            Ok(SessionEvent::Dispatched { player_idx }) if Some(player_idx) == active.seat => None,
+            Ok(SessionEvent::Dispatched { .. })
+            | Ok(SessionEvent::RolledBack { .. })
+            | Err(broadcast::error::RecvError::Lagged(_)) => self.state_message().await.ok(),
+            Ok(SessionEvent::Closed) | Err(broadcast::error::RecvError::Closed) => {
+                let active = self.active.take()?;
+                Some(ServerMessage::Closed {
+                    session_id: active.id,
+                })
+            }
+        }
+    }
+
+    /// Message to push for a proof status change, if this connection follows it.
+    fn on_proof_update(&self, update: ProofUpdate) -> Option<ServerMessage> {
+        let in_session = self
+            .active
+            .as_ref()
+            .is_some_and(|active| update.session_id.as_ref() == Some(&active.id));
+        if !in_session && !self.proof_ids.contains(&update.proof_id) {
+            return None;
+        }
+
+        Some(ServerMessage::ProofStatus {
+            proof_id: update.proof_id,
+            status: update.status.into(),
+        })
+    }
+
+    async fn handle(&mut self, message: ClientMessage) -> Result<ServerMessage, ProtocolError> {
+        if !self.handshake_done && !matches!(message, ClientMessage::Hello { .. }) {
+            return Err(ProtocolError::new(
+                ErrorCode::HandshakeRequired,
+                "Send hello first",
+            ));
+        }
+
+        match message {
+            ClientMessage::Hello { version } => {
+                if version != PROTOCOL_VERSION {
+                    return Err(ProtocolError::new(
+                        ErrorCode::UnsupportedVersion,
+                        format!("Server speaks protocol version {}", PROTOCOL_VERSION),
+                    ));
+                }
+                self.handshake_done = true;
+                Ok(ServerMessage::Welcome {
+                    version: PROTOCOL_VERSION,
+                    challenge: self.challenge.clone(),
+                })
+            }
+            ClientMessage::Authenticate { address, signature } => {
+                let verified = verify_challenge(&self.challenge, &address, &signature)
+                    .map_err(|e| ProtocolError::new(ErrorCode::InvalidSignature, e))?;
+
+                // Seats are bound to the address, so leave the previous one behind
+                if self.address != Some(verified) {
+                    self.active = None;
+                }
+                self.address = Some(verified);
+                Ok(ServerMessage::Authenticated {
+                    address: format!("0x{}", hex::encode(verified)),
+                })
+            }
+            ClientMessage::JoinSession {
+                session_id,
+                game_config,
+                seed,
+            } => self.join_session(session_id, game_config, seed).await,
+            ClientMessage::Spectate { session_id } => self.spectate(session_id).await,
+            ClientMessage::Action { actions } => {
+                let (session, player_idx) = self.active_session()?;
+
+                dispatch_actions(session, actions, Seats::Owned(player_idx))
+                    .await
+                    .map_err(|e| ProtocolError::new(ErrorCode::InvalidAction, e))?;
+
+                self.state_message().await
+            }
+            ClientMessage::Proof {
+                proof_type,
+                backend,
+            } => {
+                let (session, _) = self.active_session()?;
+                if backend
+                    .as_deref()
+                    .is_some_and(|backend| !BACKEND_NAMES.contains(&backend))
+                {
+                    return Err(ProtocolError::new(
+                        ErrorCode::InvalidMessage,
+                        "Unknown prover backend",
+                    ));
+                }
+                let (session_id, stdin) = {
+                    let session_guard = session.lock().await;
+                    (session_guard.id(), session_guard.sp1_stdin())
+                };
+
+                let proof_id = self.ctx.prove_queue.enqueue_task();
+                submit_proof_job(
+                    &self.ctx.prove_queue,
+                    &self.ctx.tx_jobs,
+                    proof_id.clone(),
+                    ProofRequest::new(session_id, proof_type, stdin, backend),
+                )
+                .map_err(|e| ProtocolError::new(ErrorCode::Internal, e))?;
+
+                self.proof_id = Some(proof_id.clone());
+                self.proof_ids.insert(proof_id.clone());
+                Ok(ServerMessage::ProofQueued { proof_id })
+            }
+            ClientMessage::ProofStatus { proof_id } => {
+                let proof_id = proof_id.or_else(|| self.proof_id.clone()).ok_or_else(|| {
+                    ProtocolError::new(ErrorCode::ProofNotFound, "No proof requested")
+                })?;
+                let status = self.ctx.prove_queue.get_status(&proof_id).ok_or_else(|| {
+                    ProtocolError::new(ErrorCode::ProofNotFound, "Proof not found")
+                })?;
+
+                Ok(ServerMessage::ProofStatus {
+                    proof_id,
+                    status: status.into(),
+                })
+            }
+            ClientMessage::Undo => {
+                let (session, player_idx) = self.active_session()?;
+                session
+                    .lock()
+                    .await
+                    .undo(player_idx)
+                    .map_err(|e| ProtocolError::new(ErrorCode::InvalidAction, e))?;
+
+                self.state_message().await
+            }
+            ClientMessage::Reveal => {
+                let (session, _) = self.active_session()?;
+                let reveal = session
+                    .lock()
+                    .await
+                    .reveal()
+                    .map_err(|e| ProtocolError::new(ErrorCode::GameNotOver, e))?;
+
+                Ok(ServerMessage::Revealed {
+                    reveal: serde_json::to_value(reveal)
+                        .map_err(|e| ProtocolError::new(ErrorCode::Internal, e.to_string()))?,
+                })
+            }
+            ClientMessage::CloseSession => {
+                let (session, player_idx) = self.active_session()?;
+                let close = {
+                    let mut session_guard = session.lock().await;
+                    session_guard.leave(player_idx) || session_guard.public_state().is_terminal()
+                };
+                let session_id = self.active.take().ok_or_else(no_active_session)?.id;
+
+                // Other players are still in the game, only this connection goes
+                if !close {
+                    return Ok(ServerMessage::Left { session_id });
+                }
+
+                self.ctx
+                    .session_manager
+                    .lock()
+                    .await
+                    .close_session(&session_id)
+                    .await
+                    .map_err(|e| ProtocolError::new(ErrorCode::Internal, e))?;
+                Ok(ServerMessage::Closed { session_id })
+            }
+        }
+    }
+
+    async fn join_session(
+        &mut self,
+        session_id: Option<String>,
+        game_config: Option<Vec<u8>>,
+        seed: Option<String>,
+    ) -> Result<ServerMessage, ProtocolError> {
+        let address = self.address.ok_or_else(|| {
+            ProtocolError::new(ErrorCode::AuthenticationRequired, "Authenticate first")
+        })?;
+        let seed = seed
+            .map(|seed| decode_seed(&seed))
+            .transpose()
+            .map_err(|e| ProtocolError::new(ErrorCode::InvalidSeed, e))?;
+
+        let session_manager = self.ctx.session_manager.clone();
+        let mut session_manager = session_manager.lock().await;
+
+        let session_id = match session_id {
+            Some(session_id) => session_id,
+            None => session_manager
+                .create_session_with_config(self.ctx.reducer, game_config.unwrap_or_default())
+                .await
+                .map_err(|e| ProtocolError::new(ErrorCode::InvalidMessage, e))?,
+        };
+
+        let session = session_manager
+            .get_session(&session_id)
+            .await
+            .ok_or_else(|| ProtocolError::new(ErrorCode::SessionNotFound, "Session not found"))?;
+
+        let (player_idx, server_commitment, seed_commitment, events) = {
+            let mut session_guard = session.lock().await;
+            let player_idx = session_guard
+                .join_as(address, seed)
+                .map_err(|e| ProtocolError::new(ErrorCode::InvalidSeed, e))?;
+            (
+                player_idx,
+                session_guard.server_commitment(),
+                session_guard
+                    .seed_commitment(player_idx)
+                    .unwrap_or_default(),
+                session_guard.subscribe(),
+            )
+        };
+        self.active = Some(ActiveSession {
+            id: session_id.clone(),
+            session,
+            seat: Some(player_idx),
+            events,
+        });
+
+        Ok(ServerMessage::Joined {
+            session_id,
+            player_idx,
+            server_commitment: format!("0x{}", hex::encode(server_commitment)),
+            seed_commitment: format!("0x{}", hex::encode(seed_commitment)),
+        })
+    }
+
+    async fn spectate(&mut self, session_id: String) -> Result<ServerMessage, ProtocolError> {
+        let session = self
+            .ctx
+            .session_manager
+            .lock()
+            .await
+            .get_session(&session_id)
+            .await
+            .ok_or_else(|| ProtocolError::new(ErrorCode::SessionNotFound, "Session not found"))?;
+
+        let (public_state, events) = {
+            let session_guard = session.lock().await;
+            (session_guard.spectator_json(), session_guard.subscribe())
+        };
+        self.active = Some(ActiveSession {
+            id: session_id.clone(),
+            session,
+            seat: None,
+            events,
+        });
+
+        Ok(ServerMessage::Spectating {
+            session_id,
+            public_state,
+        })
+    }
+}
+
+/// Id of a request that failed to parse, so the error can still be correlated.
+fn raw_request_id(text: &str) -> Option<u64> {
+    serde_json::from_str::<serde_json::Value>(text)
+        .ok()?
+        .get("id")?
+        .as_u64()
+}
+
+/// Wait for the next event of the joined session, or forever without one.
+async fn next_session_event<PublicState, PrivateState, GameAction>(
+    active: &mut Option<ActiveSession<PublicState, PrivateState, GameAction>>,
+) -> Result<SessionEvent, broadcast::error::RecvError>
+where
+    PublicState: Serialize + Default + Send + Sync,
+    PrivateState: Default + Send + Sync,
+    GameAction: TurboActionSerialization + Send + Sync,
+{
+    match active {
+        Some(active) => active.events.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+enum Incoming {
+    Frame(Option<Result<Message, warp::Error>>),
+    Session(Result<SessionEvent, broadcast::error::RecvError>),
+    Proof(Result<ProofUpdate, broadcast::error::RecvError>),
+}
+
+/// Serve one WebSocket connection until the client disconnects.
+///
+/// Besides replies to requests, the server pushes messages without an `id`:
+/// the new state whenever another player of the session acts, the status of
+/// proofs of the session or requested on the connection, and `closed` when
+/// the session goes away.
+pub async fn handle_socket<PublicState, PrivateState, GameAction>(
+    websocket: WebSocket,
+    ctx: WsContext<PublicState, PrivateState, GameAction>,
+) where
+    PublicState: Serialize
+        + Default
+        + TurboInitState
+        + PlayerView<PrivateState>
+        + UndoRules
+        + HasTerminalState
+        + Clone
+        + Send
+        + Sync,
+    PrivateState: Default + Serialize + Clone + Send + Sync,
+    GameAction: TurboActionSerialization + Send + Sync,
+{
+    let (mut tx, mut rx) = websocket.split();
+    let mut proof_updates = ctx.prove_queue.subscribe();
+    let mut connection = Connection::new(ctx);
+
+    loop {
+        let incoming = tokio::select! {
+            frame = rx.next() => Incoming::Frame(frame),
+            event = next_session_event(&mut connection.active) => Incoming::Session(event),
+            update = proof_updates.recv() => Incoming::Proof(update),
+        };
+
+        let reply = match incoming {
+            Incoming::Frame(Some(Ok(msg))) => {
+                if msg.is_close() {
+                    break;
+                }
+                let Ok(text) = msg.to_str() else {
+                    continue;
+                };
+
+                match serde_json::from_str::<ClientEnvelope>(text) {
+                    Ok(envelope) => ServerEnvelope::reply(
+                        envelope.id,
+                        connection.handle(envelope.message).await,
+                    ),
+                    Err(e) => ServerEnvelope::reply(
+                        raw_request_id(text),
+                        Err(ProtocolError::new(ErrorCode::InvalidMessage, e.to_string())),
+                    ),
+                }
+            }
+            Incoming::Frame(_) => break,
+            Incoming::Session(event) => match connection.on_session_event(event).await {
+                Some(message) => ServerEnvelope::reply(None, Ok(message)),
+                None => continue,
+            },
+            Incoming::Proof(Ok(update)) => match connection.on_proof_update(update) {
+                Some(message) => ServerEnvelope::reply(None, Ok(message)),
+                None => continue,
+            },
+            Incoming::Proof(Err(_)) => continue,
+        };
+
+        let reply = serde_json::to_string(&reply).unwrap_or_else(|_| {
+            String::from(
+                r#"{"type":"error","code":"internal","message":"Failed to serialize response"}"#,
+            )
+        });
+        if tx.send(Message::text(reply)).await.is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game_lib::{
+        action::GameAction,
+        reducer::reducer,
+        state::{GamePrivateState, GamePublicState},
+    };
+
+    #[tokio::test]
+    async fn truncated_action_is_an_error_frame() {
+        let ctx = WsContext::<GamePublicState, GamePrivateState, GameAction> {
+            reducer,
+            session_manager: Arc::new(Mutex::new(SessionManager::new())),
+            prove_queue: Arc::new(ProveQueue::new()),
+            tx_jobs: Arc::new(mpsc::unbounded_channel().0),
+        };
+        let mut connection = Connection::new(ctx);
+        connection
+            .handle(ClientMessage::Hello {
+                version: PROTOCOL_VERSION,
+            })
+            .await
+            .unwrap();
+        // Seats only need an address, skip signing the challenge
+        connection.address = Some([1; 20]);
+        connection
+            .handle(ClientMessage::JoinSession {
+                session_id: None,
+                game_config: None,
+                seed: None,
+            })
+            .await
+            .unwrap();
+
+        let error = connection
+            .handle(ClientMessage::Action {
+                actions: json!("0x00"),
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::InvalidAction);
+        assert_eq!(error.message, "Truncated action");
+
+        // The connection keeps serving the seat
+        connection
+            .handle(ClientMessage::Action {
+                actions: json!("0x0003"),
+            })
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,248 @@
+use std::{fmt, path::PathBuf, str::FromStr};
+
+use alloy::{
+    contract::{CallBuilder, CallDecoder},
+    network::Network,
+    primitives::{Address, FixedBytes},
+    providers::{DynProvider, Provider, ProviderBuilder},
+    signers::local::PrivateKeySigner,
+};
+use sp1_sdk::{HashableKey, SP1VerifyingKey};
+
+use super::ConnectXGame;
+
+/// `ConnectXGame` deployment on Base Sepolia.
+pub const CONNECTX_GAME_ADDRESS: &str = "0x52781fD1B028a0cc04C650E4053F8E0cc624628E";
+
+pub const BASE_SEPOLIA_CHAIN_ID: u64 = 84532;
+
+pub const ANVIL_CHAIN_ID: u64 = 31337;
+
+/// Key of the first prefunded account of an Anvil node.
+const ANVIL_DEV_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+/// Where the key signing ZeroMind transactions comes from.
+#[derive(Clone, PartialEq, Eq)]
+pub enum SignerSource {
+    /// Hex private key in the environment variable of that name.
+    Env(String),
+    /// Encrypted JSON keystore.
+    Keystore { path: PathBuf, password: String },
+    /// First prefunded account of a local Anvil node.
+    Dev,
+}
+
+// Written by hand so the keystore password never ends up in logs
+impl fmt::Debug for SignerSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignerSource::Env(var) => f.debug_tuple("Env").field(var).finish(),
+            SignerSource::Keystore { path, .. } => f
+                .debug_struct("Keystore")
+                .field("path", path)
+                .field("password", &"<redacted>")
+                .finish(),
+            SignerSource::Dev => f.write_str("Dev"),
+        }
+    }
+}
+
+impl SignerSource {
+    pub fn signer(&self) -> Result<PrivateKeySigner, &'static str> {
+        match self {
+            SignerSource::Env(var) => std::env::var(var)
+                .map_err(|_| "Signer key variable not set")?
+                .parse()
+                .map_err(|_| "Invalid signer key"),
+            SignerSource::Keystore { path, password } => {
+                PrivateKeySigner::decrypt_keystore(path, password)
+                    .map_err(|_| "Failed to decrypt keystore")
+            }
+            SignerSource::Dev => Ok(ANVIL_DEV_KEY.parse().expect("Invalid dev key")),
+        }
+    }
+}
+
+/// Overrides of the gas values estimated by the provider.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GasSettings {
+    pub gas_limit: Option<u64>,
+    pub max_fee_per_gas: Option<u128>,
+    pub max_priority_fee_per_gas: Option<u128>,
+}
+
+impl GasSettings {
+    fn apply<P, D, N>(&self, mut call: CallBuilder<P, D, N>) -> CallBuilder<P, D, N>
+    where
+        P: Provider<N>,
+        D: CallDecoder,
+        N: Network,
+    {
+        if let Some(gas_limit) = self.gas_limit {
+            call = call.gas(gas_limit);
+        }
+        if let Some(max_fee_per_gas) = self.max_fee_per_gas {
+            call = call.max_fee_per_gas(max_fee_per_gas);
+        }
+        if let Some(max_priority_fee_per_gas) = self.max_priority_fee_per_gas {
+            call = call.max_priority_fee_per_gas(max_priority_fee_per_gas);
+        }
+        call
+    }
+}
+
+/// `ConnectXGame` deployment to submit agents and games to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZeromindConfig {
+    pub contract: Address,
+    /// Chain the RPC endpoint must serve, checked when connecting.
+    pub chain_id: u64,
+    pub rpc_url: String,
+    pub signer: SignerSource,
+    pub gas: GasSettings,
+}
+
+impl Default for ZeromindConfig {
+    fn default() -> Self {
+        Self {
+            contract: Address::from_str(CONNECTX_GAME_ADDRESS).unwrap(),
+            chain_id: BASE_SEPOLIA_CHAIN_ID,
+            rpc_url: "https://sepolia.base.org".to_string(),
+            signer: SignerSource::Env("NETWORK_PRIVATE_KEY".to_string()),
+            gas: GasSettings::default(),
+        }
+    }
+}
+
+fn env_parse<T: FromStr>(name: &str) -> Result<Option<T>, &'static str> {
+    match std::env::var(name) {
+        Ok(value) => value
+            .parse()
+            .map(Some)
+            .map_err(|_| "Invalid ZeroMind environment variable"),
+        Err(_) => Ok(None),
+    }
+}
+
+impl ZeromindConfig {
+    /// Local Anvil node on the default port, signing with its first account.
+    pub fn anvil(contract: Address) -> Self {
+        Self {
+            contract,
+            chain_id: ANVIL_CHAIN_ID,
+            rpc_url: "http://127.0.0.1:8545".to_string(),
+            signer: SignerSource::Dev,
+            gas: GasSettings::default(),
+        }
+    }
+
+    /// Build the config from the environment, defaulting to the Base Sepolia
+    /// deployment.
+    ///
+    /// Reads `ZEROMIND_CONTRACT`, `ZEROMIND_CHAIN_ID` and `RPC_URL`. The key
+    /// comes from `ZEROMIND_KEYSTORE` unlocked with
+    /// `ZEROMIND_KEYSTORE_PASSWORD` when set, and from `NETWORK_PRIVATE_KEY`
+    /// otherwise. `ZEROMIND_GAS_LIMIT`, `ZEROMIND_MAX_FEE_PER_GAS` and
+    /// `ZEROMIND_MAX_PRIORITY_FEE_PER_GAS` override the estimated gas values.
+    pub fn from_env() -> Result<Self, &'static str> {
+        let default = Self::default();
+
+        let signer = match std::env::var("ZEROMIND_KEYSTORE") {
+            Ok(path) => SignerSource::Keystore {
+                path: path.into(),
+                password: std::env::var("ZEROMIND_KEYSTORE_PASSWORD").unwrap_or_default(),
+            },
+            Err(_) => default.signer,
+        };
+
+        Ok(Self {
+            contract: env_parse("ZEROMIND_CONTRACT")?.unwrap_or(default.contract),
+            chain_id: env_parse("ZEROMIND_CHAIN_ID")?.unwrap_or(default.chain_id),
+            rpc_url: std::env::var("RPC_URL").unwrap_or(default.rpc_url),
+            signer,
+            gas: GasSettings {
+                gas_limit: env_parse("ZEROMIND_GAS_LIMIT")?,
+                max_fee_per_gas: env_parse("ZEROMIND_MAX_FEE_PER_GAS")?,
+                max_priority_fee_per_gas: env_parse("ZEROMIND_MAX_PRIORITY_FEE_PER_GAS")?,
+            },
+        })
+    }
+
+    /// Build the signer and provider once and check the endpoint serves
+    /// `chain_id`.
+    pub async fn connect(&self) -> Result<ZeromindContract, String> {
+        let signer = self.signer.signer()?;
+        let provider = ProviderBuilder::new()
+            .wallet(signer)
+            .connect_http(self.rpc_url.parse().map_err(|_| "Invalid RPC URL")?)
+            .erased();
+
+        let chain_id = provider
+            .get_chain_id()
+            .await
+            .map_err(|e| format!("Failed to get chain id: {}", e))?;
+        if chain_id != self.chain_id {
+            return Err(format!(
+                "RPC endpoint serves chain {}, expected {}",
+                chain_id, self.chain_id
+            ));
+        }
+
+        Ok(ZeromindContract {
+            contract: ConnectXGame::new(self.contract, provider),
+            gas: self.gas.clone(),
+        })
+    }
+}
+
+/// Connection to a `ConnectXGame` deployment.
+pub struct ZeromindContract {
+    contract: ConnectXGame::ConnectXGameInstance<DynProvider>,
+    gas: GasSettings,
+}
+
+impl ZeromindContract {
+    pub async fn register_agent(&self, vk: &SP1VerifyingKey, name: &str) -> Result<(), String> {
+        let call = self.gas.apply(self.contract.registerAgent(
+            FixedBytes::<32>::from_slice(&vk.bytes32_raw()),
+            name.to_string(),
+        ));
+
+        let receipt = call
+            .send()
+            .await
+            .map_err(|e| format!("Failed to register agent: {}", e))?
+            .get_receipt()
+            .await
+            .map_err(|e| format!("Failed to get register agent receipt: {}", e))?;
+        if !receipt.status() {
+            return Err("Register agent transaction reverted".to_string());
+        }
+
+        Ok(())
+    }
+
+    pub async fn play_game(&self, game: ConnectXGame::playGameCall) -> Result<(), String> {
+        let call = self.gas.apply(self.contract.playGame(
+            game._agent1,
+            game._agent2,
+            game._agent1proof,
+            game._agent2proof,
+            game._gameProof,
+            game._gamePublicValues,
+        ));
+
+        let receipt = call
+            .send()
+            .await
+            .map_err(|e| format!("Failed to play game: {}", e))?
+            .get_receipt()
+            .await
+            .map_err(|e| format!("Failed to get play game receipt: {}", e))?;
+        if !receipt.status() {
+            return Err("Play game transaction reverted".to_string());
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,657 @@
+use std::{
+    fmt, fs,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use alloy::{
+    // monolithic re-export of all the pieces
+    primitives::{Bytes, FixedBytes},
+    sol_types::{sol, SolCall},
+};
+use alloy_sol_types::SolValue;
+use futures_util::future::try_join3;
+use rand::{thread_rng, Rng};
+use sp1_sdk::{EnvProver, HashableKey, SP1ProofWithPublicValues, SP1Stdin};
+use substrate_bn::*;
+use tokio::sync::{mpsc, Semaphore};
+use turbo_program::{
+    context::TurboActionContext,
+    crypto::bn_serialize::bn254_export_affine_g1_memcpy,
+    metadata::{PlayerMetadata, ServerMetadata},
+    program::TurboReducer,
+    public_values::{match_digest, AgentPublicValues},
+    traits::{
+        HasActions, HasCurrentPlayer, HasTerminalState, TurboActionSerialization, TurboInitState,
+    },
+    zeromind::ZeroMindAgent,
+};
+
+use crate::key_cache::{KeyCache, ProgramKeys};
+use crate::proof::decode_public_values;
+
+mod config;
+pub mod tournament;
+
+pub use config::{GasSettings, SignerSource, ZeromindConfig, ZeromindContract};
+
+// Load ABI from file
+sol!(
+    #[sol(rpc)]
+    ConnectXGame,
+    "src/connectx_abi.json"
+);
+
+pub struct ZeromindAgentSubmission<PublicState, GameAction> {
+    agent: ZeroMindAgent<PublicState, GameAction>,
+    elf: Arc<Vec<u8>>,
+    name: String,
+}
+
+impl<PublicState, GameAction> ZeromindAgentSubmission<PublicState, GameAction> {
+    pub fn new(agent: ZeroMindAgent<PublicState, GameAction>, elf: &[u8], name: &str) -> Self {
+        Self {
+            agent,
+            elf: Arc::new(elf.to_vec()),
+            name: name.to_string(),
+        }
+    }
+}
+
+/// Keys of `elf` from the shared key cache, set up on a miss.
+fn setup_circuit(client: &EnvProver, elf: &[u8]) -> Result<ProgramKeys, &'static str> {
+    KeyCache::global().get_or_setup(elf, || client.setup(elf))
+}
+
+fn make_metadata(players: usize) -> (ServerMetadata, Vec<PlayerMetadata>) {
+    make_metadata_with_rng(&mut thread_rng(), players, Vec::new())
+}
+
+/// Draw the seeds of a match from `rng`, so a seeded `rng` replays the same match.
+fn make_metadata_with_rng<R: Rng>(
+    rng: &mut R,
+    players: usize,
+    game_config: Vec<u8>,
+) -> (ServerMetadata, Vec<PlayerMetadata>) {
+    let server_random_seed = AffineG1::one() * Fr::random(rng);
+    let server_metadata = ServerMetadata {
+        random_seed: bn254_export_affine_g1_memcpy(&server_random_seed),
+        match_nonce: rng.gen(),
+        game_config,
+    };
+
+    let player_metadata = (0..players)
+        .map(|_| {
+            let player_random_seed = AffineG1::one() * Fr::random(rng);
+            PlayerMetadata {
+                random_seed: bn254_export_affine_g1_memcpy(&player_random_seed),
+                address: [0; 20],
+            }
+        })
+        .collect();
+
+    (server_metadata, player_metadata)
+}
+
+/// Play a game between `agents`, one per seat, returning the final state and the
+/// player tagged action stream read by the game program.
+fn zeromind_run_agent_inner<PublicState, PrivateState, GameAction>(
+    reducer: TurboReducer<PublicState, PrivateState, GameAction>,
+    agents: &[ZeroMindAgent<PublicState, GameAction>],
+    server_metadata: &ServerMetadata,
+    player_metadata: &[PlayerMetadata],
+) -> Result<(PublicState, Vec<u8>), String>
+where
+    PublicState: TurboInitState + HasTerminalState + HasCurrentPlayer,
+    PrivateState: Default,
+    GameAction: TurboActionSerialization,
+{
+    let mut public_state = PublicState::init_state(&server_metadata.game_config)
+        .expect("Failed to initialize public state");
+    let mut private_state = PrivateState::default();
+    let mut actions_game: Vec<u8> = Vec::new();
+
+    // Create the contexts of every seat
+    let mut player_contexts: Vec<TurboActionContext> = player_metadata
+        .iter()
+        .enumerate()
+        .map(|(i, metadata)| TurboActionContext::new(server_metadata, metadata, i))
+        .collect();
+
+    // Run the game
+    while !public_state.is_terminal() {
+        let current_player = public_state
+            .current_player()
+            .ok_or_else(|| "Game has no player to act".to_string())?;
+        let context = &mut player_contexts[current_player];
+
+        let action = agents[current_player](&public_state, context);
+
+        reducer(&mut public_state, &mut private_state, &action, context).map_err(|e| {
+            format!(
+                "Agent {} played an invalid action: {}",
+                current_player,
+                Into::<&'static str>::into(e)
+            )
+        })?;
+
+        actions_game.push(current_player as u8); // player turn
+        actions_game.extend(action.serialize());
+    }
+
+    Ok((public_state, actions_game))
+}
+
+pub fn zeromind_run_agent<PublicState, PrivateState, GameAction>(
+    reducer: TurboReducer<PublicState, PrivateState, GameAction>,
+    agents: &[ZeroMindAgent<PublicState, GameAction>],
+) -> Result<PublicState, String>
+where
+    PublicState: TurboInitState + HasTerminalState + HasCurrentPlayer,
+    PrivateState: Default,
+    GameAction: TurboActionSerialization,
+{
+    let (server_metadata, player_metadata) = make_metadata(agents.len());
+
+    zeromind_run_agent_inner(reducer, agents, &server_metadata, &player_metadata)
+        .map(|(public_state, _)| public_state)
+}
+
+/// How far a submission goes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum SubmitMode {
+    /// Register both agents, generate the Groth16 proofs and call `playGame`.
+    #[default]
+    OnChain,
+    /// Stop once the game and agent programs were executed and cross checked.
+    CheckOnly,
+    /// Generate the proofs and write them to `dir` along with the `playGame`
+    /// calldata instead of sending transactions. Run with `SP1_PROVER=mock`
+    /// to skip real proving.
+    DryRun { dir: PathBuf },
+}
+
+/// Step of an agent submission.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubmitStage {
+    /// Setting up the proving keys.
+    Setup,
+    /// Connecting to the contract deployment.
+    Connect,
+    /// Registering the agents on chain.
+    Register,
+    /// Playing the match on the host.
+    Play,
+    /// Executing the game program and checking its public values.
+    CheckGame,
+    /// Executing the program of the agent in `seat` and checking its public values.
+    CheckAgent { seat: u8 },
+    /// Generating the proofs.
+    Prove,
+    /// Writing the proofs and calldata of a dry run.
+    Write,
+    /// Sending `playGame`.
+    Submit,
+}
+
+impl fmt::Display for SubmitStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubmitStage::Setup => write!(f, "setup"),
+            SubmitStage::Connect => write!(f, "connection"),
+            SubmitStage::Register => write!(f, "agent registration"),
+            SubmitStage::Play => write!(f, "match"),
+            SubmitStage::CheckGame => write!(f, "game check"),
+            SubmitStage::CheckAgent { seat } => write!(f, "agent {} check", seat + 1),
+            SubmitStage::Prove => write!(f, "proving"),
+            SubmitStage::Write => write!(f, "dry run output"),
+            SubmitStage::Submit => write!(f, "game submission"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SubmitError {
+    pub stage: SubmitStage,
+    pub message: String,
+}
+
+impl SubmitError {
+    fn new(stage: SubmitStage, message: impl Into<String>) -> Self {
+        Self {
+            stage,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for SubmitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} failed: {}", self.stage, self.message)
+    }
+}
+
+impl std::error::Error for SubmitError {}
+
+/// Program run and proved for a submission.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubmitProgram {
+    Game,
+    Agent { seat: u8 },
+}
+
+impl SubmitProgram {
+    fn check_stage(self) -> SubmitStage {
+        match self {
+            SubmitProgram::Game => SubmitStage::CheckGame,
+            SubmitProgram::Agent { seat } => SubmitStage::CheckAgent { seat },
+        }
+    }
+}
+
+impl fmt::Display for SubmitProgram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubmitProgram::Game => write!(f, "Game"),
+            SubmitProgram::Agent { seat } => write!(f, "Agent {}", seat + 1),
+        }
+    }
+}
+
+/// Progress of a submission, as each program goes through setup, execution
+/// and proving.
+#[derive(Debug, Clone)]
+pub enum SubmitProgress {
+    KeysReady {
+        program: SubmitProgram,
+        vkey: String,
+    },
+    Verified {
+        program: SubmitProgram,
+        cycles: u64,
+    },
+    Proved {
+        program: SubmitProgram,
+        elapsed: Duration,
+    },
+}
+
+impl fmt::Display for SubmitProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubmitProgress::KeysReady { program, vkey } => write!(f, "{} vkey: {}", program, vkey),
+            SubmitProgress::Verified { program, cycles } => {
+                write!(f, "{} verified ({} cycles)", program, cycles)
+            }
+            SubmitProgress::Proved { program, elapsed } => {
+                write!(f, "{} proof generated in {:?}", program, elapsed)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SubmitOptions {
+    pub mode: SubmitMode,
+    /// Prover calls running at once on the blocking pool.
+    pub max_parallel: usize,
+    /// Receives progress updates, which are printed when `None`.
+    pub progress: Option<mpsc::UnboundedSender<SubmitProgress>>,
+}
+
+impl Default for SubmitOptions {
+    fn default() -> Self {
+        Self {
+            mode: SubmitMode::OnChain,
+            max_parallel: 3,
+            progress: None,
+        }
+    }
+}
+
+impl SubmitOptions {
+    fn report(&self, progress: SubmitProgress) {
+        match &self.progress {
+            // Nobody listening is fine
+            Some(tx) => {
+                let _ = tx.send(progress);
+            }
+            None => println!("{}", progress),
+        }
+    }
+}
+
+/// Program that passed its checks, ready to be proved.
+struct CheckedProgram {
+    program: SubmitProgram,
+    keys: ProgramKeys,
+    stdin: Arc<SP1Stdin>,
+}
+
+/// Run a blocking prover call on tokio's blocking pool once `permits` allows.
+/// The permit moves into the call, so it is held until the call returns even
+/// if the caller stops waiting for it.
+async fn run_blocking<T: Send + 'static>(
+    permits: &Arc<Semaphore>,
+    stage: SubmitStage,
+    f: impl FnOnce() -> T + Send + 'static,
+) -> Result<T, SubmitError> {
+    let permit = permits
+        .clone()
+        .acquire_owned()
+        .await
+        .map_err(|_| SubmitError::new(stage, "Prover pool closed"))?;
+    tokio::task::spawn_blocking(move || {
+        let _permit = permit;
+        f()
+    })
+    .await
+    .map_err(|_| SubmitError::new(stage, "Prover task panicked"))
+}
+
+/// Set up, execute and check `program`. `check` compares the public values
+/// with the match played on the host.
+async fn check_program(
+    client: Arc<EnvProver>,
+    permits: &Arc<Semaphore>,
+    options: &SubmitOptions,
+    program: SubmitProgram,
+    elf: Arc<Vec<u8>>,
+    stdin: SP1Stdin,
+    check: impl FnOnce(&[u8]) -> Result<(), &'static str>,
+) -> Result<CheckedProgram, SubmitError> {
+    let keys = {
+        let (client, elf) = (client.clone(), elf.clone());
+        run_blocking(permits, SubmitStage::Setup, move || {
+            setup_circuit(&client, &elf)
+        })
+        .await?
+        .map_err(|e| SubmitError::new(SubmitStage::Setup, e))?
+    };
+    options.report(SubmitProgress::KeysReady {
+        program,
+        vkey: keys.1.bytes32(),
+    });
+
+    let stdin = Arc::new(stdin);
+    let (public_values, report) = {
+        let stdin = stdin.clone();
+        run_blocking(permits, program.check_stage(), move || {
+            client.execute(&elf, &stdin).run()
+        })
+        .await?
+        .map_err(|_| SubmitError::new(program.check_stage(), "Failed to execute circuit"))?
+    };
+    check(public_values.as_slice()).map_err(|e| SubmitError::new(program.check_stage(), e))?;
+    options.report(SubmitProgress::Verified {
+        program,
+        cycles: report.total_instruction_count(),
+    });
+
+    Ok(CheckedProgram {
+        program,
+        keys,
+        stdin,
+    })
+}
+
+async fn prove_program(
+    client: Arc<EnvProver>,
+    permits: &Arc<Semaphore>,
+    options: &SubmitOptions,
+    checked: &CheckedProgram,
+) -> Result<SP1ProofWithPublicValues, SubmitError> {
+    let program = checked.program;
+    let start = Instant::now();
+    let proof = {
+        let (keys, stdin) = (checked.keys.clone(), checked.stdin.clone());
+        run_blocking(permits, SubmitStage::Prove, move || {
+            client.prove(&keys.0, &stdin).groth16().run()
+        })
+        .await?
+        .map_err(|e| SubmitError::new(SubmitStage::Prove, format!("{}: {}", program, e)))?
+    };
+    options.report(SubmitProgress::Proved {
+        program,
+        elapsed: start.elapsed(),
+    });
+
+    Ok(proof)
+}
+
+pub async fn zeromind_submit_agent<PublicState, PrivateState, GameAction>(
+    client: Arc<EnvProver>,
+    config: &ZeromindConfig,
+    reducer: TurboReducer<PublicState, PrivateState, GameAction>,
+    game_elf: Arc<Vec<u8>>,
+    agent1: ZeromindAgentSubmission<PublicState, GameAction>,
+    agent2: ZeromindAgentSubmission<PublicState, GameAction>,
+) -> Result<PublicState, String>
+where
+    PublicState: TurboInitState
+        + HasTerminalState
+        + HasCurrentPlayer
+        + HasActions
+        + SolValue
+        + From<<<PublicState as SolValue>::SolType as alloy_sol_types::SolType>::RustType>,
+    PrivateState: Default,
+    GameAction: TurboActionSerialization,
+{
+    zeromind_submit_agent_with_options(
+        client,
+        config,
+        reducer,
+        game_elf,
+        agent1,
+        agent2,
+        SubmitOptions::default(),
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Same as `zeromind_submit_agent` with explicit `options`.
+///
+/// The game and both agents are executed and checked concurrently on the
+/// blocking pool, then proved concurrently once every check passed, so a
+/// failed check never leaves a proof running. The first failed check drops
+/// the remaining checks: calls waiting for the pool never start, while calls
+/// already running finish in the background, holding their pool slot.
+pub async fn zeromind_submit_agent_with_options<PublicState, PrivateState, GameAction>(
+    client: Arc<EnvProver>,
+    config: &ZeromindConfig,
+    reducer: TurboReducer<PublicState, PrivateState, GameAction>,
+    game_elf: Arc<Vec<u8>>,
+    agent1: ZeromindAgentSubmission<PublicState, GameAction>,
+    agent2: ZeromindAgentSubmission<PublicState, GameAction>,
+    options: SubmitOptions,
+) -> Result<PublicState, SubmitError>
+where
+    PublicState: TurboInitState
+        + HasTerminalState
+        + HasCurrentPlayer
+        + HasActions
+        + SolValue
+        + From<<<PublicState as SolValue>::SolType as alloy_sol_types::SolType>::RustType>,
+    PrivateState: Default,
+    GameAction: TurboActionSerialization,
+{
+    // Connect first so a bad deployment config fails before any proving
+    let contract = match options.mode {
+        SubmitMode::OnChain => Some(
+            config
+                .connect()
+                .await
+                .map_err(|e| SubmitError::new(SubmitStage::Connect, e))?,
+        ),
+        _ => None,
+    };
+
+    let (server_metadata, player_metadata) = make_metadata(2);
+
+    let (result, actions_game) = zeromind_run_agent_inner(
+        reducer,
+        &[agent1.agent, agent2.agent],
+        &server_metadata,
+        &player_metadata,
+    )
+    .map_err(|e| SubmitError::new(SubmitStage::Play, e))?;
+
+    let actions = result.actions();
+    let expected_state = PublicState::abi_encode(&result);
+
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&server_metadata);
+    stdin.write(&player_metadata);
+
+    let mut stdin_game = stdin.clone();
+    stdin_game.write(&actions_game);
+
+    // Match commitment shared by the game proof and both agent proofs
+    let digest = match_digest(&server_metadata, &player_metadata, &actions_game);
+
+    let mut stdin0 = stdin.clone();
+    stdin0.write(&actions);
+    stdin0.write(&0u8);
+
+    let mut stdin1 = stdin.clone();
+    stdin1.write(&actions);
+    stdin1.write(&1u8);
+
+    let check_game = |public_values: &[u8]| -> Result<(), &'static str> {
+        let (envelope, game_state) = decode_public_values::<PublicState>(public_values)
+            .map_err(|_| "Failed to decode game state")?;
+
+        // Check if moves match
+        if game_state.actions() != actions {
+            return Err("Game moves do not match expected moves");
+        }
+
+        // Check if there is a winner (not 0) and matches result
+        if !game_state.is_terminal() {
+            return Err("Game did not reach terminal state");
+        }
+
+        // Check if the result matches
+        if expected_state != envelope.public_state.as_ref() {
+            return Err("Game result does not match expected result");
+        }
+
+        // Check if the game is bound to this match
+        if envelope.match_digest != digest {
+            return Err("Game match digest does not match");
+        }
+
+        Ok(())
+    };
+
+    // Verify the public values match actions and the match commitment
+    let check_agent = |seat: u8| {
+        let expected =
+            AgentPublicValues::abi_encode(&AgentPublicValues::new(digest, seat, &actions));
+        move |public_values: &[u8]| -> Result<(), &'static str> {
+            if public_values != expected {
+                return Err("Agent public values do not match");
+            }
+            Ok(())
+        }
+    };
+
+    let permits = Arc::new(Semaphore::new(options.max_parallel.max(1)));
+    let (game, agent1_checked, agent2_checked) = try_join3(
+        check_program(
+            client.clone(),
+            &permits,
+            &options,
+            SubmitProgram::Game,
+            game_elf,
+            stdin_game,
+            check_game,
+        ),
+        check_program(
+            client.clone(),
+            &permits,
+            &options,
+            SubmitProgram::Agent { seat: 0 },
+            agent1.elf.clone(),
+            stdin0,
+            check_agent(0),
+        ),
+        check_program(
+            client.clone(),
+            &permits,
+            &options,
+            SubmitProgram::Agent { seat: 1 },
+            agent2.elf.clone(),
+            stdin1,
+            check_agent(1),
+        ),
+    )
+    .await?;
+
+    if options.mode == SubmitMode::CheckOnly {
+        return Ok(result);
+    }
+
+    let (game_proof, agent1_proof, agent2_proof) = try_join3(
+        prove_program(client.clone(), &permits, &options, &game),
+        prove_program(client.clone(), &permits, &options, &agent1_checked),
+        prove_program(client, &permits, &options, &agent2_checked),
+    )
+    .await?;
+
+    let play_game = ConnectXGame::playGameCall {
+        _agent1: FixedBytes::<32>::from_slice(&agent1_checked.keys.1.bytes32_raw()),
+        _agent2: FixedBytes::<32>::from_slice(&agent2_checked.keys.1.bytes32_raw()),
+        _agent1proof: Bytes::from(agent1_proof.bytes()),
+        _agent2proof: Bytes::from(agent2_proof.bytes()),
+        _gameProof: Bytes::from(game_proof.bytes()),
+        _gamePublicValues: Bytes::from(game_proof.public_values.to_vec()),
+    };
+
+    match options.mode {
+        SubmitMode::DryRun { dir } => {
+            let fail = |message: &str| SubmitError::new(SubmitStage::Write, message);
+
+            fs::create_dir_all(&dir).map_err(|_| fail("Failed to create output directory"))?;
+            for (name, proof) in [
+                ("game", &game_proof),
+                ("agent1", &agent1_proof),
+                ("agent2", &agent2_proof),
+            ] {
+                proof
+                    .save(dir.join(format!("{}.proof", name)))
+                    .map_err(|_| fail("Failed to save proof"))?;
+            }
+
+            let calldata = format!("0x{}", hex::encode(SolCall::abi_encode(&play_game)));
+            fs::write(dir.join("play_game.calldata"), calldata)
+                .map_err(|_| fail("Failed to save calldata"))?;
+
+            println!("Proofs and calldata written to {}", dir.display());
+        }
+        _ => {
+            if let Some(contract) = &contract {
+                // Register only once every proof is ready, so a failed
+                // submission leaves nothing on chain
+                for (keys, name) in [
+                    (&agent1_checked.keys, &agent1.name),
+                    (&agent2_checked.keys, &agent2.name),
+                ] {
+                    contract
+                        .register_agent(&keys.1, name)
+                        .await
+                        .map_err(|e| SubmitError::new(SubmitStage::Register, e))?;
+                }
+
+                contract
+                    .play_game(play_game)
+                    .await
+                    .map_err(|e| SubmitError::new(SubmitStage::Submit, e))?;
+            }
+        }
+    }
+
+    Ok(result)
+}
@@ -0,0 +1,467 @@
+use std::{cmp::Reverse, collections::HashSet, fmt};
+
+use alloy::primitives::{keccak256, U256};
+use rand::{rngs::StdRng, SeedableRng};
+use turbo_program::{
+    program::TurboReducer,
+    traits::{
+        HasCurrentPlayer, HasTerminalState, HasWinner, TurboActionSerialization, TurboInitState,
+    },
+    zeromind::ZeroMindAgent,
+};
+
+use super::{make_metadata_with_rng, zeromind_run_agent_inner};
+
+/// Rating of a new agent, `ConnectXGame.DEFAULT_ELO`.
+pub const DEFAULT_ELO: u64 = 1200;
+
+/// `ConnectXGame.K_FACTOR`.
+pub const K_FACTOR: u64 = 32;
+
+const WAD: u64 = 1_000_000_000_000_000_000;
+
+/// `FixedPointMathLib.rpow` of solmate: `x` to the integer power `n`, both
+/// scaled by `scalar`, rounding every step to nearest.
+fn rpow(mut x: U256, mut n: U256, scalar: U256) -> U256 {
+    if x.is_zero() {
+        return if n.is_zero() { scalar } else { U256::ZERO };
+    }
+
+    let mut z = if n.bit(0) { x } else { scalar };
+    let half = scalar >> 1;
+    n >>= 1;
+    while !n.is_zero() {
+        x = (x * x + half) / scalar;
+        if n.bit(0) {
+            z = (z * x + half) / scalar;
+        }
+        n >>= 1;
+    }
+    z
+}
+
+fn abs_diff(a: U256, b: U256) -> U256 {
+    if a > b {
+        a - b
+    } else {
+        b - a
+    }
+}
+
+/// Ratings of two agents after a game, `winner` being the seat that won or
+/// `None` for a draw. Follows `ConnectXGame.updateElo` step by step, including
+/// its fixed point rounding, so offline ratings match the ones on chain.
+///
+/// The contract raises an unscaled 10 to a scaled exponent, so `pow` rounds to
+/// zero for any rating gap and seat 1 is expected to win with certainty,
+/// whatever the ratings. Once ratings differ, seat 1 never gains: a seat 1 win
+/// changes nothing, while seat 2 takes `K_FACTOR` points for a win and half of
+/// them for a draw. Ratings mostly follow seat order rather than skill, so
+/// standings rank by score first.
+pub fn update_elo(elo1: u64, elo2: u64, winner: Option<usize>) -> (u64, u64) {
+    let scale = U256::from(WAD);
+
+    let elo_diff = U256::from(elo1.abs_diff(elo2));
+    let pow = rpow(U256::from(10), elo_diff * scale / U256::from(400), scale);
+    let expected1 = scale * scale / (scale + pow);
+    let expected2 = scale - expected1;
+
+    let (actual1, actual2) = match winner {
+        Some(0) => (scale, U256::ZERO),
+        Some(_) => (U256::ZERO, scale),
+        None => (scale / U256::from(2), scale / U256::from(2)),
+    };
+
+    let k_factor = U256::from(K_FACTOR);
+    let delta1 = (k_factor * abs_diff(actual1, expected1) / scale).to::<u64>();
+    let delta2 = (k_factor * abs_diff(actual2, expected2) / scale).to::<u64>();
+
+    if actual1 > expected1 {
+        (elo1 + delta1, elo2.saturating_sub(delta2))
+    } else {
+        (elo1.saturating_sub(delta1), elo2 + delta2)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TournamentFormat {
+    /// Every agent meets every other agent once.
+    RoundRobin,
+    /// `rounds` rounds, each pairing agents with close scores that have not
+    /// met yet. With an odd pool the lowest ranked agent without a bye sits
+    /// out the round and scores a win.
+    Swiss { rounds: usize },
+}
+
+#[derive(Debug, Clone)]
+pub struct TournamentConfig {
+    pub format: TournamentFormat,
+    /// Games played by every pairing, alternating which agent moves first.
+    pub games_per_pairing: usize,
+    /// Seed of the seed schedule. The same seed and agents replay the same
+    /// tournament.
+    pub seed: u64,
+    /// Game config of every match, empty for the game's default setup.
+    pub game_config: Vec<u8>,
+}
+
+impl Default for TournamentConfig {
+    fn default() -> Self {
+        Self {
+            format: TournamentFormat::RoundRobin,
+            games_per_pairing: 2,
+            seed: 0,
+            game_config: Vec::new(),
+        }
+    }
+}
+
+pub struct TournamentAgent<PublicState, GameAction> {
+    pub name: String,
+    pub agent: ZeroMindAgent<PublicState, GameAction>,
+}
+
+impl<PublicState, GameAction> TournamentAgent<PublicState, GameAction> {
+    pub fn new(name: &str, agent: ZeroMindAgent<PublicState, GameAction>) -> Self {
+        Self {
+            name: name.to_string(),
+            agent,
+        }
+    }
+}
+
+/// Win, draw and loss counts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Record {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl Record {
+    fn add(&mut self, won: Option<bool>) {
+        match won {
+            Some(true) => self.wins += 1,
+            Some(false) => self.losses += 1,
+            None => self.draws += 1,
+        }
+    }
+
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}/{}", self.wins, self.draws, self.losses)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Standing {
+    /// Index of the agent in the pool.
+    pub agent: usize,
+    pub name: String,
+    pub elo: u64,
+    /// Two per win or bye, one per draw.
+    pub half_points: u32,
+    pub record: Record,
+    pub first_seat: Record,
+    pub second_seat: Record,
+    pub byes: u32,
+}
+
+impl Standing {
+    fn new(agent: usize, name: &str) -> Self {
+        Self {
+            agent,
+            name: name.to_string(),
+            elo: DEFAULT_ELO,
+            half_points: 0,
+            record: Record::default(),
+            first_seat: Record::default(),
+            second_seat: Record::default(),
+            byes: 0,
+        }
+    }
+
+    pub fn score(&self) -> f64 {
+        self.half_points as f64 / 2.0
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TournamentGame {
+    pub round: usize,
+    /// Agents by seat, the first one moves first.
+    pub seats: [usize; 2],
+    /// Agent that won, `None` for a draw.
+    pub winner: Option<usize>,
+    /// Seed the match metadata was drawn from.
+    pub seed: [u8; 32],
+}
+
+#[derive(Debug, Clone)]
+pub struct TournamentResult {
+    /// Sorted by score, then rating.
+    pub standings: Vec<Standing>,
+    pub games: Vec<TournamentGame>,
+}
+
+impl fmt::Display for TournamentResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self
+            .standings
+            .iter()
+            .map(|standing| standing.name.len())
+            .max()
+            .unwrap_or_default()
+            .max(5);
+
+        writeln!(
+            f,
+            "{:>3}  {:<width$}  {:>5}  {:>5}  {:>4}  {:>4}  {:>4}  {:>10}  {:>10}",
+            "#", "Agent", "Elo", "Score", "W", "D", "L", "1st W/D/L", "2nd W/D/L"
+        )?;
+        for (rank, standing) in self.standings.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>3}  {:<width$}  {:>5}  {:>5.1}  {:>4}  {:>4}  {:>4}  {:>10}  {:>10}",
+                rank + 1,
+                standing.name,
+                standing.elo,
+                standing.score(),
+                standing.record.wins,
+                standing.record.draws,
+                standing.record.losses,
+                standing.first_seat.to_string(),
+                standing.second_seat.to_string(),
+            )?;
+        }
+
+        // Advantage of moving first over the whole tournament
+        let mut first_seat = Record::default();
+        for game in &self.games {
+            first_seat.add(game.winner.map(|winner| winner == game.seats[0]));
+        }
+        write!(
+            f,
+            "First seat W/D/L over {} games: {}",
+            first_seat.games(),
+            first_seat
+        )
+    }
+}
+
+/// Seed of game `game_idx` of a tournament seeded with `seed`.
+fn game_seed(seed: u64, game_idx: usize) -> [u8; 32] {
+    let mut data = seed.to_be_bytes().to_vec();
+    data.extend((game_idx as u64).to_be_bytes());
+    keccak256(data).into()
+}
+
+fn pair_key(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+/// Rounds of a round robin between `n` agents (circle method).
+fn round_robin_rounds(n: usize) -> Vec<Vec<(usize, usize)>> {
+    // Odd pools get an empty slot, whoever faces it sits out the round
+    let mut slots: Vec<Option<usize>> = (0..n).map(Some).collect();
+    if n % 2 == 1 {
+        slots.push(None);
+    }
+    let m = slots.len();
+
+    let mut rounds = Vec::new();
+    for _ in 1..m {
+        rounds.push(
+            (0..m / 2)
+                .filter_map(|i| Some((slots[i]?, slots[m - 1 - i]?)))
+                .collect(),
+        );
+        slots[1..].rotate_right(1);
+    }
+    rounds
+}
+
+/// Pairings of the next Swiss round, along with the agent sitting out.
+fn swiss_pairings(
+    standings: &[Standing],
+    met: &HashSet<(usize, usize)>,
+) -> (Vec<(usize, usize)>, Option<usize>) {
+    let mut order: Vec<usize> = (0..standings.len()).collect();
+    order.sort_by_key(|&i| Reverse((standings[i].half_points, standings[i].elo)));
+
+    let bye = (order.len() % 2 == 1).then(|| {
+        let pos = order
+            .iter()
+            .rposition(|&i| standings[i].byes == 0)
+            .unwrap_or(order.len() - 1);
+        order.remove(pos)
+    });
+
+    let mut pairs = Vec::new();
+    while !order.is_empty() {
+        let a = order.remove(0);
+        // Closest ranked opponent not met yet, a rematch when all were met
+        let pos = order
+            .iter()
+            .position(|&b| !met.contains(&pair_key(a, b)))
+            .unwrap_or(0);
+        pairs.push((a, order.remove(pos)));
+    }
+
+    (pairs, bye)
+}
+
+/// Play a tournament between two seat `agents` on the host, without proving
+/// anything, and rank them with the rating math of `ConnectXGame`.
+pub fn run_tournament<PublicState, PrivateState, GameAction>(
+    reducer: TurboReducer<PublicState, PrivateState, GameAction>,
+    agents: &[TournamentAgent<PublicState, GameAction>],
+    config: &TournamentConfig,
+) -> Result<TournamentResult, String>
+where
+    PublicState: TurboInitState + HasTerminalState + HasCurrentPlayer + HasWinner,
+    PrivateState: Default,
+    GameAction: TurboActionSerialization,
+{
+    if agents.len() < 2 {
+        return Err("A tournament needs at least two agents".to_string());
+    }
+
+    let mut standings: Vec<Standing> = agents
+        .iter()
+        .enumerate()
+        .map(|(i, agent)| Standing::new(i, &agent.name))
+        .collect();
+    let mut games = Vec::new();
+    let mut met = HashSet::new();
+
+    let schedule = round_robin_rounds(agents.len());
+    let rounds = match config.format {
+        TournamentFormat::RoundRobin => schedule.len(),
+        TournamentFormat::Swiss { rounds } => rounds,
+    };
+
+    for round in 0..rounds {
+        let pairings = match config.format {
+            TournamentFormat::RoundRobin => schedule[round].clone(),
+            TournamentFormat::Swiss { .. } => {
+                let (pairings, bye) = swiss_pairings(&standings, &met);
+                if let Some(bye) = bye {
+                    standings[bye].byes += 1;
+                    standings[bye].half_points += 2;
+                }
+                pairings
+            }
+        };
+
+        for (a, b) in pairings {
+            // The agent which moved first less often starts the pairing
+            let (a, b) = if standings[b].first_seat.games() < standings[a].first_seat.games() {
+                (b, a)
+            } else {
+                (a, b)
+            };
+
+            for game in 0..config.games_per_pairing {
+                let seats = if game % 2 == 0 { [a, b] } else { [b, a] };
+
+                let seed = game_seed(config.seed, games.len());
+                let (server_metadata, player_metadata) = make_metadata_with_rng(
+                    &mut StdRng::from_seed(seed),
+                    2,
+                    config.game_config.clone(),
+                );
+
+                let (public_state, _) = zeromind_run_agent_inner(
+                    reducer,
+                    &[agents[seats[0]].agent, agents[seats[1]].agent],
+                    &server_metadata,
+                    &player_metadata,
+                )
+                .map_err(|e| {
+                    format!(
+                        "{} vs {} in round {}: {}",
+                        agents[seats[0]].name,
+                        agents[seats[1]].name,
+                        round + 1,
+                        e
+                    )
+                })?;
+
+                let winner_seat = public_state.winner();
+                let (elo1, elo2) = update_elo(
+                    standings[seats[0]].elo,
+                    standings[seats[1]].elo,
+                    winner_seat,
+                );
+                standings[seats[0]].elo = elo1;
+                standings[seats[1]].elo = elo2;
+
+                for (seat, &agent) in seats.iter().enumerate() {
+                    let won = winner_seat.map(|winner| winner == seat);
+                    let standing = &mut standings[agent];
+                    standing.half_points += match won {
+                        Some(true) => 2,
+                        Some(false) => 0,
+                        None => 1,
+                    };
+                    standing.record.add(won);
+                    if seat == 0 {
+                        standing.first_seat.add(won);
+                    } else {
+                        standing.second_seat.add(won);
+                    }
+                }
+
+                games.push(TournamentGame {
+                    round,
+                    seats,
+                    winner: winner_seat.map(|winner| seats[winner]),
+                    seed,
+                });
+            }
+
+            met.insert(pair_key(a, b));
+        }
+    }
+
+    standings.sort_by_key(|standing| Reverse((standing.half_points, standing.elo)));
+    Ok(TournamentResult { standings, games })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn elo_matches_contract() {
+        assert_eq!(update_elo(1200, 1200, Some(0)), (1216, 1184));
+        assert_eq!(update_elo(1200, 1200, Some(1)), (1184, 1216));
+        assert_eq!(update_elo(1200, 1200, None), (1200, 1200));
+
+        // Any gap gives seat 1 an expected score of 1, even as the underdog
+        for (elo1, elo2) in [(1216, 1184), (1184, 1216)] {
+            assert_eq!(update_elo(elo1, elo2, Some(0)), (elo1, elo2));
+            assert_eq!(update_elo(elo1, elo2, Some(1)), (elo1 - 32, elo2 + 32));
+            assert_eq!(update_elo(elo1, elo2, None), (elo1 - 16, elo2 + 16));
+        }
+    }
+
+    #[test]
+    fn round_robin_meets_everyone_once() {
+        for n in [2, 5, 6] {
+            let mut met = HashSet::new();
+            for round in round_robin_rounds(n) {
+                for (a, b) in round {
+                    assert!(met.insert(pair_key(a, b)));
+                }
+            }
+            assert_eq!(met.len(), n * (n - 1) / 2);
+        }
+    }
+}